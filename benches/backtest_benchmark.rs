@@ -19,7 +19,7 @@ impl Strategy for SimpleBuyAndHold {
         Ok(())
     }
 
-    fn next(&mut self, bar: &OHLCV, _index: usize) -> backtesting::Result<Vec<Order>> {
+    fn next(&mut self, _bar: &OHLCV, _index: usize) -> backtesting::Result<Vec<Order>> {
         if !self.bought {
             self.bought = true;
             Ok(vec![Order::new(
@@ -66,7 +66,7 @@ fn benchmark_backtest_small(c: &mut Criterion) {
     c.bench_function("backtest_small_252_days", |b| {
         b.iter(|| {
             let data_clone = data.clone();
-            let mut backtest = Backtest::new(black_box(&data_clone), black_box(config.clone()));
+            let backtest = Backtest::new(black_box(&data_clone), black_box(config.clone()));
             let strategy = SimpleBuyAndHold::new();
             black_box(backtest.run(strategy).unwrap())
         })
@@ -80,7 +80,7 @@ fn benchmark_backtest_medium(c: &mut Criterion) {
     c.bench_function("backtest_medium_1260_days", |b| {
         b.iter(|| {
             let data_clone = data.clone();
-            let mut backtest = Backtest::new(black_box(&data_clone), black_box(config.clone()));
+            let backtest = Backtest::new(black_box(&data_clone), black_box(config.clone()));
             let strategy = SimpleBuyAndHold::new();
             black_box(backtest.run(strategy).unwrap())
         })
@@ -95,7 +95,7 @@ fn benchmark_backtest_large(c: &mut Criterion) {
     c.bench_function("backtest_large_2520_days", |b| {
         b.iter(|| {
             let data_clone = data.clone();
-            let mut backtest = Backtest::new(black_box(&data_clone), black_box(config.clone()));
+            let backtest = Backtest::new(black_box(&data_clone), black_box(config.clone()));
             let strategy = SimpleBuyAndHold::new();
             black_box(backtest.run(strategy).unwrap())
         })
@@ -76,7 +76,7 @@ fn main() -> backtesting::Result<()> {
     };
     
     // Create and run backtest
-    let mut backtest = Backtest::new(&data, config);
+    let backtest = Backtest::new(&data, config);
     let results = backtest.run(BuyAndHold::new())?;
     
     // Print results
@@ -113,11 +113,11 @@ fn main() -> backtesting::Result<()> {
     println!("Number of Trades: {}", results.trades.len());
     if !results.trades.is_empty() {
         println!("Win Rate: {:.2}%", results.win_rate * 100.0);
-        println!("Best Trade: ${:.2}", results.best_trade);
-        println!("Worst Trade: ${:.2}", results.worst_trade);
-        println!("Average Trade: ${:.2}", results.avg_trade);
+        println!("Best Trade: ${:.2}", results.best_trade.unwrap_or_default());
+        println!("Worst Trade: ${:.2}", results.worst_trade.unwrap_or_default());
+        println!("Average Trade: ${:.2}", results.avg_trade.unwrap_or_default());
         println!("Profit Factor: {:.2}", results.profit_factor);
-        println!("Expectancy: ${:.2}", results.expectancy);
+        println!("Expectancy: ${:.2}", results.expectancy.unwrap_or_default());
         println!("SQN: {:.2}", results.sqn);
         println!("Avg Trade Duration: {} days", results.avg_trade_duration.num_days());
     } else {
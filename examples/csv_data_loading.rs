@@ -62,17 +62,7 @@ impl Strategy for SimpleMAStrategy {
                 println!("BUY at {:.2} (MA: {:.2}) - size: {:.2}", bar.close, ma, position_size);
             } else if bar.close < ma * 0.98 && self.position {
                 self.position = false;
-                // For sell orders, we'll sell all shares. The backtest engine will handle the actual position size
-                orders.push(Order::new(
-                    OrderSide::Sell,
-                    OrderType::Market,
-                    1000.0, // Large number to ensure we sell the full position
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                ));
+                orders.push(self.close());
                 println!("SELL at {:.2} (MA: {:.2})", bar.close, ma);
             }
 
@@ -121,7 +111,7 @@ fn main() -> backtesting::Result<()> {
 
     // Run backtest
     println!("\nRunning backtest...");
-    let mut backtest = Backtest::new(&data, config);
+    let backtest = Backtest::new(&data, config);
     let results = backtest.run(strategy)?;
 
     // Print results
@@ -156,7 +146,7 @@ fn main() -> backtesting::Result<()> {
     println!("Number of Trades: {}", results.trades.len());
     if !results.trades.is_empty() {
         println!("Win Rate: {:.2}%", results.win_rate * 100.0);
-        println!("Average Trade: ${:.2}", results.avg_trade);
+        println!("Average Trade: ${:.2}", results.avg_trade.unwrap_or_default());
         println!("Profit Factor: {:.2}", results.profit_factor);
     }
 
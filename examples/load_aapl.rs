@@ -27,16 +27,7 @@ impl Strategy for SimpleStrategy {
             )])
         } else if index == 6 {
             println!("SELL at {:.2}", bar.close);
-            Ok(vec![Order::new(
-                OrderSide::Sell,
-                OrderType::Market,
-                60.0,
-                None,
-                None,
-                None,
-                None,
-                None,
-            )])
+            Ok(vec![self.close()])
         } else {
             Ok(vec![])
         }
@@ -78,7 +69,7 @@ fn main() -> backtesting::Result<()> {
     };
 
     // Run backtest
-    let mut backtest = Backtest::new(&data, config);
+    let backtest = Backtest::new(&data, config);
     let results = backtest.run(SimpleStrategy)?;
 
     println!("\n=== Results ===");
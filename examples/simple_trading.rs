@@ -40,21 +40,12 @@ impl Strategy for SimpleTradingStrategy {
                 None,
                 None,
             )])
-        } else if self.position && index % 20 == 0 {
+        } else if self.position && index.is_multiple_of(20) {
             self.position = false;
             self.trade_count += 1;
             println!("SELL at price: {:.2} (day {})", bar.close, index);
 
-            Ok(vec![Order::new(
-                OrderSide::Sell,
-                OrderType::Market,
-                50.0,
-                None,
-                None,
-                None,
-                None,
-                None,
-            )])
+            Ok(vec![self.close()])
         } else {
             Ok(vec![])
         }
@@ -99,7 +90,7 @@ fn main() -> backtesting::Result<()> {
     };
 
     // Create and run backtest
-    let mut backtest = Backtest::new(&data, config);
+    let backtest = Backtest::new(&data, config);
     let results = backtest.run(SimpleTradingStrategy::new())?;
 
     // Print results
@@ -142,11 +133,11 @@ fn main() -> backtesting::Result<()> {
     println!("Number of Trades: {}", results.trades.len());
     if !results.trades.is_empty() {
         println!("Win Rate: {:.2}%", results.win_rate * 100.0);
-        println!("Best Trade: ${:.2}", results.best_trade);
-        println!("Worst Trade: ${:.2}", results.worst_trade);
-        println!("Average Trade: ${:.2}", results.avg_trade);
+        println!("Best Trade: ${:.2}", results.best_trade.unwrap_or_default());
+        println!("Worst Trade: ${:.2}", results.worst_trade.unwrap_or_default());
+        println!("Average Trade: ${:.2}", results.avg_trade.unwrap_or_default());
         println!("Profit Factor: {:.2}", results.profit_factor);
-        println!("Expectancy: ${:.2}", results.expectancy);
+        println!("Expectancy: ${:.2}", results.expectancy.unwrap_or_default());
         println!("SQN: {:.2}", results.sqn);
         println!(
             "Avg Trade Duration: {} days",
@@ -161,6 +152,7 @@ fn main() -> backtesting::Result<()> {
         let plot_config = PlotConfig {
             width: 1200,
             height: 800,
+            candlestick: true,
             show_trades: true,
             show_equity_curve: true,
             show_drawdown: false,
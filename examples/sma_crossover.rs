@@ -1,5 +1,5 @@
 use backtesting::data::DataLoader;
-use backtesting::indicators::{Indicator, SimpleMovingAverage};
+use backtesting::indicators::{Crossover, Indicator, SimpleMovingAverage};
 use backtesting::{Order, OrderSide, OrderType};
 use backtesting::types::OHLCV;
 use backtesting::{Backtest, BacktestConfig, Strategy};
@@ -7,7 +7,7 @@ use backtesting::{Backtest, BacktestConfig, Strategy};
 struct SMACrossover {
     fast_sma: SimpleMovingAverage,
     slow_sma: SimpleMovingAverage,
-    position: bool,
+    crossover: Crossover,
 }
 
 impl SMACrossover {
@@ -15,7 +15,7 @@ impl SMACrossover {
         Self {
             fast_sma: SimpleMovingAverage::new(fast_period),
             slow_sma: SimpleMovingAverage::new(slow_period),
-            position: false,
+            crossover: Crossover::new(),
         }
     }
 }
@@ -35,10 +35,10 @@ impl Strategy for SMACrossover {
         match (fast_sma, slow_sma) {
             (Some(fast), Some(slow)) => {
                 let mut orders = Vec::with_capacity(1);
+                self.crossover.update(fast, slow);
 
                 // Buy signal: fast SMA crosses above slow SMA
-                if fast > slow && !self.position {
-                    self.position = true;
+                if self.crossover.crossed_above() {
                     orders.push(Order::new(
                         OrderSide::Buy,
                         OrderType::Market,
@@ -55,18 +55,9 @@ impl Strategy for SMACrossover {
                     );
                 }
                 // Sell signal: fast SMA crosses below slow SMA
-                else if fast < slow && self.position {
-                    self.position = false;
-                    orders.push(Order::new(
-                        OrderSide::Sell,
-                        OrderType::Market,
-                        50.0,
-                        None,
-                        None,
-                        None,
-                        None,
-                        None,
-                    ));
+                else if self.crossover.crossed_below() {
+                    // Close the long; a cross below before any entry closes nothing
+                    orders.push(self.close());
                     println!(
                         "SELL signal at {:.2} (Fast: {:.2}, Slow: {:.2})",
                         bar.close, fast, slow
@@ -109,7 +100,7 @@ fn main() -> backtesting::Result<()> {
     let strategy = SMACrossover::new(20, 50);
 
     // Create and run backtest
-    let mut backtest = Backtest::new(&data, config);
+    let backtest = Backtest::new(&data, config);
     let results = backtest.run(strategy)?;
 
     // Print results
@@ -152,11 +143,11 @@ fn main() -> backtesting::Result<()> {
     println!("Number of Trades: {}", results.trades.len());
     if !results.trades.is_empty() {
         println!("Win Rate: {:.2}%", results.win_rate * 100.0);
-        println!("Best Trade: ${:.2}", results.best_trade);
-        println!("Worst Trade: ${:.2}", results.worst_trade);
-        println!("Average Trade: ${:.2}", results.avg_trade);
+        println!("Best Trade: ${:.2}", results.best_trade.unwrap_or_default());
+        println!("Worst Trade: ${:.2}", results.worst_trade.unwrap_or_default());
+        println!("Average Trade: ${:.2}", results.avg_trade.unwrap_or_default());
         println!("Profit Factor: {:.2}", results.profit_factor);
-        println!("Expectancy: ${:.2}", results.expectancy);
+        println!("Expectancy: ${:.2}", results.expectancy.unwrap_or_default());
         println!("SQN: {:.2}", results.sqn);
         println!(
             "Avg Trade Duration: {} days",
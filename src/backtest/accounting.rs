@@ -0,0 +1,237 @@
+//! Fill and position accounting shared by `Backtest` and `PortfolioBacktest`, so both
+//! engines size, charge and settle fills the same way
+
+use super::{BacktestConfig, PartialFills};
+use crate::order::{Order, OrderSide, SizeUnit};
+use crate::position::Position;
+use crate::trade::Trade;
+use crate::types::OHLCV;
+use chrono::{DateTime, Utc};
+
+/// `size` units changing hands at `price`
+pub(crate) struct Fill {
+    pub size: f64,
+    pub price: f64,
+    pub bar_index: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Lots closed off a position by one exit fill
+pub(crate) struct Exit {
+    /// One trade per closed lot
+    pub trades: Vec<Trade>,
+    /// Cash the exit settles before commission: a long's sale proceeds, or a short's
+    /// P&L as its sale proceeds were never credited
+    pub settlement: f64,
+    pub commission: f64,
+    pub was_long: bool,
+}
+
+/// Cash plus the open positions. Longs were paid for in cash; shorts contribute only
+/// their open P&L.
+pub(crate) fn equity<'a>(cash: f64, positions: impl Iterator<Item = &'a Position>) -> f64 {
+    cash + positions
+        .map(|p| if p.is_short() { p.pl() } else { p.value() })
+        .sum::<f64>()
+}
+
+/// `price` moved against a fill by the symbol's slippage model when `apply_slippage`
+/// is set
+pub(crate) fn slipped_price(
+    config: &BacktestConfig,
+    symbol: Option<&str>,
+    side: OrderSide,
+    price: f64,
+    size: f64,
+    bar: &OHLCV,
+    apply_slippage: bool,
+) -> f64 {
+    if apply_slippage {
+        config
+            .slippage_for(symbol)
+            .adjust_fill(side, price, size, bar)
+    } else {
+        price
+    }
+}
+
+/// Units an order sized as a fraction of `equity` trades at `price`.
+///
+/// The fraction covers the fill's commission and slippage too, so `0.9999` can deploy
+/// nearly all equity without the order being rejected for lack of cash.
+pub(crate) fn equity_fraction_units(
+    config: &BacktestConfig,
+    order: &Order,
+    equity: f64,
+    price: f64,
+    bar: &OHLCV,
+    apply_slippage: bool,
+) -> f64 {
+    let budget = order.size * equity;
+    let estimate = budget / price;
+    let symbol = order.symbol.as_deref();
+    let fill_price = slipped_price(
+        config,
+        symbol,
+        order.side,
+        price,
+        estimate,
+        bar,
+        apply_slippage,
+    );
+    let commission = config.commission_for(symbol, estimate, fill_price);
+
+    ((budget - commission) / fill_price).max(0.0)
+}
+
+/// Open positions and equity an opening fill is checked against
+pub(crate) struct BuyingPower {
+    open_notional: f64,
+    equity: f64,
+}
+
+impl BuyingPower {
+    pub fn of<'a, I>(cash: f64, positions: I) -> Self
+    where
+        I: Iterator<Item = &'a Position> + Clone,
+    {
+        Self {
+            open_notional: positions.clone().map(|p| p.value().abs()).sum(),
+            equity: equity(cash, positions),
+        }
+    }
+
+    /// Whether the open positions plus `size` units at `price` stay within the buying
+    /// power of `equity / margin` once the fill's commission is paid
+    fn fits(&self, config: &BacktestConfig, symbol: Option<&str>, size: f64, price: f64) -> bool {
+        let commission = config.commission_for(symbol, size, price);
+        (self.open_notional + size * price) * config.margin + commission <= self.equity
+    }
+
+    /// Units of an opening fill of `size` that go through: all of them when they fit,
+    /// otherwise none under `PartialFills::Reject` and the largest affordable part
+    /// under the other policies. Bisection keeps it exact for commission models with
+    /// minimums and tiers.
+    pub fn fillable_units(
+        &self,
+        config: &BacktestConfig,
+        symbol: Option<&str>,
+        size: f64,
+        price: f64,
+    ) -> f64 {
+        if self.fits(config, symbol, size, price) {
+            return size;
+        }
+        if config.partial_fills == PartialFills::Reject {
+            return 0.0;
+        }
+
+        let (mut low, mut high) = (0.0, size);
+        for _ in 0..64 {
+            let mid = (low + high) / 2.0;
+            if self.fits(config, symbol, mid, price) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        // Rounding dust in the equity is not worth a fill
+        if low > size * 1e-9 {
+            config.tradable_units(low)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Add an opening fill of `order` on `side` to `position`, an empty one for a new
+/// position, and return the fill's commission.
+///
+/// The fill becomes a new lot, the order's tag, symbol, stops and trail fill in what
+/// the position lacks, and the initial margin the fill ties up is set aside.
+pub(crate) fn open(
+    position: &mut Position,
+    config: &BacktestConfig,
+    order: &Order,
+    side: OrderSide,
+    fill: &Fill,
+) -> f64 {
+    let commission = config.commission_for(order.symbol.as_deref(), fill.size, fill.price);
+    let signed_size = match side {
+        OrderSide::Buy => fill.size,
+        OrderSide::Sell => -fill.size,
+    };
+
+    position.add_lot(
+        signed_size,
+        fill.price,
+        fill.timestamp,
+        fill.bar_index,
+        commission,
+        order.tag.clone(),
+    );
+    position.tag = position.tag.take().or_else(|| order.tag.clone());
+    position.symbol = position.symbol.take().or_else(|| order.symbol.clone());
+    position.sl = position.sl.or(order.sl);
+    position.tp = position.tp.or(order.tp);
+    position.trail_percent = position.trail_percent.or(order.trail_percent);
+    position.trail_amount = position.trail_amount.or(order.trail_amount);
+    position.trail_stop(fill.price, fill.price);
+    position.margin_used += fill.size * fill.price * config.margin;
+
+    commission
+}
+
+/// Close up to `fill.size` units of `position`, taking them off its lots in
+/// `lot_matching` order. The caller removes the position once its size is zero.
+pub(crate) fn close(position: &mut Position, config: &BacktestConfig, fill: &Fill) -> Exit {
+    let close_size = fill.size.min(position.size.abs());
+    let was_long = position.is_long();
+    let (sl, tp) = (position.sl, position.tp);
+    let commission = config.commission_for(position.symbol.as_deref(), close_size, fill.price);
+
+    let lots = position.take_lots(close_size, config.lot_matching);
+    let mut trades = Vec::with_capacity(lots.len());
+    let mut short_pl = 0.0;
+    for lot in lots {
+        let mut trade = Trade::new(
+            lot.entry_bar,
+            lot.entry_price,
+            lot.entry_time,
+            lot.size,
+            sl,
+            tp,
+            lot.tag,
+        );
+        trade.entry_commission = lot.entry_commission;
+        trade.exit_commission = commission * lot.size.abs() / close_size;
+        trade.close(Some(fill.bar_index), fill.price, fill.timestamp);
+        short_pl += trade.gross_pl();
+        trades.push(trade);
+    }
+
+    Exit {
+        trades,
+        settlement: if was_long {
+            close_size * fill.price
+        } else {
+            short_pl
+        },
+        commission,
+        was_long,
+    }
+}
+
+/// Record on `order` the attempted `fill`, of which `unfilled` units didn't go through
+/// for lack of buying power. An order cut short is left `PartiallyFilled`, with its
+/// `size` converted to units so `remaining_size()` is what's left to fill.
+pub(crate) fn settle_order(order: &mut Order, fill: &Fill, unfilled: f64) {
+    order.size = order.filled_size + fill.size;
+    order.size_unit = SizeUnit::Units;
+    order.fill(
+        fill.size - unfilled,
+        fill.price,
+        fill.bar_index,
+        fill.timestamp,
+    );
+}
\ No newline at end of file
@@ -0,0 +1,237 @@
+use super::{
+    BacktestConfig, CommissionModel, DurationUnit, FillTiming, IntrabarPath, PartialFills,
+    PerformanceFee, ReturnBasis, SlippageModel, TradeSink,
+};
+use crate::error::BacktestError;
+use crate::position::LotMatching;
+use std::sync::Arc;
+
+/// Chainable construction of a `BacktestConfig`, starting from the defaults.
+///
+/// ```ignore
+/// let config = BacktestConfig::builder()
+///     .initial_cash(50_000.0)
+///     .commission(0.001)
+///     .margin(0.5)
+///     .build()?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BacktestConfigBuilder {
+    config: BacktestConfig,
+}
+
+impl BacktestConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn initial_cash(mut self, initial_cash: f64) -> Self {
+        self.config.initial_cash = initial_cash;
+        self
+    }
+
+    pub fn commission(mut self, commission: f64) -> Self {
+        self.config.commission = commission;
+        self
+    }
+
+    pub fn commission_model(mut self, model: impl CommissionModel + 'static) -> Self {
+        self.config.commission_model = Some(Arc::new(model));
+        self
+    }
+
+    /// Fee schedule for `symbol`, overriding `commission_model` and `commission`
+    pub fn commission_override(
+        mut self,
+        symbol: impl Into<String>,
+        model: impl CommissionModel + 'static,
+    ) -> Self {
+        self.config
+            .commission_overrides
+            .insert(symbol.into(), Arc::new(model));
+        self
+    }
+
+    /// Initial margin as a fraction of position value, e.g. `0.25` for 4:1 leverage
+    pub fn margin(mut self, margin: f64) -> Self {
+        self.config.margin = margin;
+        self
+    }
+
+    pub fn maintenance_margin(mut self, maintenance_margin: f64) -> Self {
+        self.config.maintenance_margin = maintenance_margin;
+        self
+    }
+
+    pub fn trade_on_open(mut self, trade_on_open: bool) -> Self {
+        self.config.trade_on_open = trade_on_open;
+        self
+    }
+
+    pub fn hedging(mut self, hedging: bool) -> Self {
+        self.config.hedging = hedging;
+        self
+    }
+
+    pub fn exclusive_orders(mut self, exclusive_orders: bool) -> Self {
+        self.config.exclusive_orders = exclusive_orders;
+        self
+    }
+
+    pub fn performance_fee(mut self, fee: PerformanceFee) -> Self {
+        self.config.performance_fee = Some(fee);
+        self
+    }
+
+    pub fn financing_rate(mut self, rate: f64) -> Self {
+        self.config.financing_rate = rate;
+        self
+    }
+
+    pub fn cash_interest_rate(mut self, rate: f64) -> Self {
+        self.config.cash_interest_rate = rate;
+        self
+    }
+
+    pub fn slippage(mut self, model: impl SlippageModel + 'static) -> Self {
+        self.config.slippage = Arc::new(model);
+        self
+    }
+
+    /// Slippage model for `symbol`, overriding `slippage`
+    pub fn slippage_override(
+        mut self,
+        symbol: impl Into<String>,
+        model: impl SlippageModel + 'static,
+    ) -> Self {
+        self.config
+            .slippage_overrides
+            .insert(symbol.into(), Arc::new(model));
+        self
+    }
+
+    pub fn fill_timing(mut self, fill_timing: FillTiming) -> Self {
+        self.config.fill_timing = fill_timing;
+        self
+    }
+
+    pub fn intrabar_path(mut self, intrabar_path: IntrabarPath) -> Self {
+        self.config.intrabar_path = intrabar_path;
+        self
+    }
+
+    pub fn duration_unit(mut self, duration_unit: DurationUnit) -> Self {
+        self.config.duration_unit = duration_unit;
+        self
+    }
+
+    pub fn return_basis(mut self, return_basis: ReturnBasis) -> Self {
+        self.config.return_basis = return_basis;
+        self
+    }
+
+    pub fn risk_free_rate(mut self, rate: f64) -> Self {
+        self.config.risk_free_rate = rate;
+        self
+    }
+
+    pub fn periods_per_year(mut self, periods: f64) -> Self {
+        self.config.periods_per_year = Some(periods);
+        self
+    }
+
+    pub fn trade_sink(mut self, sink: TradeSink) -> Self {
+        self.config.trade_sink = Some(sink);
+        self
+    }
+
+    pub fn buy_hold_net_of_commission(mut self, net: bool) -> Self {
+        self.config.buy_hold_net_of_commission = net;
+        self
+    }
+
+    pub fn lot_matching(mut self, lot_matching: LotMatching) -> Self {
+        self.config.lot_matching = lot_matching;
+        self
+    }
+
+    pub fn partial_fills(mut self, partial_fills: PartialFills) -> Self {
+        self.config.partial_fills = partial_fills;
+        self
+    }
+
+    pub fn fractional(mut self, fractional: bool) -> Self {
+        self.config.fractional = fractional;
+        self
+    }
+
+    /// Check the settings and return the config.
+    ///
+    /// Fails when `initial_cash` isn't positive, the commission rate or the maintenance
+    /// margin is negative, `margin` is outside `(0, 1]` or `periods_per_year` isn't
+    /// positive.
+    pub fn build(self) -> crate::Result<BacktestConfig> {
+        let config = self.config;
+        let invalid = |reason: &str| Err(BacktestError::InvalidConfig(reason.to_string()).into());
+
+        if !(config.initial_cash > 0.0 && config.initial_cash.is_finite()) {
+            return invalid("initial_cash must be positive");
+        }
+        if config.commission < 0.0 {
+            return invalid("commission must not be negative");
+        }
+        if !(config.margin > 0.0 && config.margin <= 1.0) {
+            return invalid("margin must be in (0, 1]; 0.25 allows 4:1 leverage");
+        }
+        if config.maintenance_margin < 0.0 {
+            return invalid("maintenance_margin must not be negative");
+        }
+        if config
+            .periods_per_year
+            .is_some_and(|periods| periods <= 0.0)
+        {
+            return invalid("periods_per_year must be positive");
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejects(builder: BacktestConfigBuilder) -> bool {
+        let error = builder.build().unwrap_err();
+        matches!(
+            error.downcast_ref::<BacktestError>(),
+            Some(BacktestError::InvalidConfig(_))
+        )
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        assert!(rejects(BacktestConfig::builder().initial_cash(-1_000.0)));
+        assert!(rejects(BacktestConfig::builder().initial_cash(0.0)));
+        assert!(rejects(BacktestConfig::builder().commission(-0.001)));
+        assert!(rejects(BacktestConfig::builder().margin(0.0)));
+        assert!(rejects(BacktestConfig::builder().margin(1.5)));
+        assert!(rejects(BacktestConfig::builder().periods_per_year(0.0)));
+    }
+
+    #[test]
+    fn valid_settings_build() {
+        let config = BacktestConfig::builder()
+            .initial_cash(50_000.0)
+            .commission(0.001)
+            .margin(0.5)
+            .periods_per_year(252.0)
+            .build()
+            .unwrap();
+
+        assert_eq!(config.initial_cash, 50_000.0);
+        assert_eq!(config.commission, 0.001);
+        assert_eq!(config.margin, 0.5);
+        assert_eq!(config.periods_per_year, Some(252.0));
+    }
+}
\ No newline at end of file
@@ -1,62 +1,208 @@
+use super::{DurationUnit, ReturnBasis};
 use crate::trade::Trade;
-use crate::types::OHLCV;
-use chrono::{DateTime, Utc};
+use crate::types::{CalendarPeriod, OHLCV};
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+use std::collections::{HashMap, HashSet};
+
+/// Fewest returns `Calculations::value_at_risk` takes a percentile of
+pub const MIN_TAIL_OBSERVATIONS: usize = 20;
+
+/// How a bar's volume is assigned to price bins in `Calculations::volume_profile_with`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolumeDistribution {
+    /// Spread evenly over the bar's high-low range, pro-rated by each bin's overlap
+    Range,
+    /// Assign entirely to the bin containing the close
+    Close,
+}
 
 pub struct Calculations;
 
 impl Calculations {
-    pub fn calculate_exposure_time(data: &[OHLCV], trades: &[Trade]) -> f64 {
-        if data.is_empty() {
+    /// Fraction of a 365.25-day year elapsed between two timestamps
+    pub fn year_fraction(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+        (to - from).num_seconds() as f64 / (365.25 * 24.0 * 60.0 * 60.0)
+    }
+
+    /// Bars per year implied by the spacing of the bars' timestamps.
+    ///
+    /// The median gap between consecutive bars picks the frequency. Daily bars give 252
+    /// trading days, or 365 when the data includes weekends (e.g. crypto). Intraday bars
+    /// multiply that by the average number of bars per calendar date, and bars a week or
+    /// more apart divide the year by the median gap. Falls back to 252 with fewer than
+    /// two distinct timestamps.
+    pub fn infer_periods_per_year(data: &[OHLCV]) -> f64 {
+        let timestamps: Vec<DateTime<Utc>> = data.iter().map(|bar| bar.timestamp).collect();
+        Self::infer_periods_per_year_from(&timestamps)
+    }
+
+    /// `infer_periods_per_year` from the bar timestamps alone
+    pub fn infer_periods_per_year_from(timestamps: &[DateTime<Utc>]) -> f64 {
+        const DAY: f64 = 24.0 * 60.0 * 60.0;
+
+        let mut gaps: Vec<i64> = timestamps
+            .windows(2)
+            .map(|w| (w[1] - w[0]).num_seconds())
+            .filter(|&gap| gap > 0)
+            .collect();
+        if gaps.is_empty() {
+            return 252.0;
+        }
+        gaps.sort_unstable();
+        let median_gap = gaps[gaps.len() / 2] as f64;
+
+        let trades_weekends = timestamps
+            .iter()
+            .any(|timestamp| matches!(timestamp.weekday(), Weekday::Sat | Weekday::Sun));
+        let trading_days = if trades_weekends { 365.0 } else { 252.0 };
+
+        if median_gap < DAY * 0.9 {
+            let dates: HashSet<_> = timestamps
+                .iter()
+                .map(|timestamp| timestamp.date_naive())
+                .collect();
+            trading_days * timestamps.len() as f64 / dates.len() as f64
+        } else if median_gap < DAY * 7.0 {
+            trading_days
+        } else {
+            365.25 * DAY / median_gap
+        }
+    }
+
+    /// Fraction of bars during which a position was open
+    pub fn calculate_exposure_time(exposed_bars: usize, total_bars: usize) -> f64 {
+        if total_bars == 0 {
             return 0.0;
         }
 
-        let mut exposed_days = 0;
-        let mut in_position = false;
-        let mut position_start = data[0].timestamp;
+        exposed_bars as f64 / total_bars as f64
+    }
 
-        // Track position changes through trades
-        for trade in trades {
-            if !in_position {
-                // Position opened
-                in_position = true;
-                position_start = trade.entry_time;
-            } else {
-                // Position closed
-                if let Some(exit_time) = trade.exit_time {
-                    exposed_days += (exit_time - position_start).num_days();
-                }
-                in_position = false;
+    /// Return of each calendar period that has bars, keyed by the period's first day.
+    ///
+    /// A period's return runs from the last equity before it (`initial_equity` for the
+    /// first) to its last equity, so the periods chain to the total return. Periods
+    /// without bars are skipped.
+    pub fn calendar_returns(
+        equity_curve: &[(DateTime<Utc>, f64)],
+        initial_equity: f64,
+        period: CalendarPeriod,
+    ) -> Vec<(NaiveDate, f64)> {
+        let mut returns = Vec::new();
+        let mut start_equity = initial_equity;
+
+        for (i, &(timestamp, equity)) in equity_curve.iter().enumerate() {
+            let period_end = match equity_curve.get(i + 1) {
+                Some(&(next, _)) => period.is_new_period(timestamp, next),
+                None => true,
+            };
+            if period_end {
+                let period_return = if start_equity > 0.0 {
+                    equity / start_equity - 1.0
+                } else {
+                    0.0
+                };
+                returns.push((period.start_date(timestamp), period_return));
+                start_equity = equity;
             }
         }
 
-        // If still in position at the end
-        if in_position {
-            exposed_days += (data.last().unwrap().timestamp - position_start).num_days();
+        returns
+    }
+
+    /// Per-bar returns of the equity curve, skipping bars that start from non-positive equity
+    pub fn period_returns(equity_curve: &[(DateTime<Utc>, f64)], basis: ReturnBasis) -> Vec<f64> {
+        equity_curve
+            .windows(2)
+            .filter(|w| w[0].1 > 0.0)
+            .map(|w| basis.period_return(w[0].1, w[1].1))
+            .collect()
+    }
+
+    /// Drawdown from the running equity peak at every point of the curve, as a
+    /// fraction: 0.0 at a new high, negative below it
+    pub fn drawdown_series(equity_curve: &[(DateTime<Utc>, f64)]) -> Vec<(DateTime<Utc>, f64)> {
+        let mut peak = f64::NEG_INFINITY;
+        equity_curve
+            .iter()
+            .map(|&(timestamp, equity)| {
+                peak = peak.max(equity);
+                let drawdown = if peak > 0.0 { equity / peak - 1.0 } else { 0.0 };
+                (timestamp, drawdown)
+            })
+            .collect()
+    }
+
+    /// Day-over-day returns of the equity at each date's last bar
+    pub fn daily_returns(equity_curve: &[(DateTime<Utc>, f64)]) -> Vec<f64> {
+        let mut daily_equity: Vec<f64> = Vec::new();
+        let mut last_date = None;
+        for &(timestamp, equity) in equity_curve {
+            let date = timestamp.date_naive();
+            match daily_equity.last_mut() {
+                Some(last) if last_date == Some(date) => *last = equity,
+                _ => daily_equity.push(equity),
+            }
+            last_date = Some(date);
         }
 
-        let total_days =
-            (data.last().unwrap().timestamp - data.first().unwrap().timestamp).num_days();
-        if total_days > 0 {
-            exposed_days as f64 / total_days as f64
-        } else {
-            0.0
+        daily_equity
+            .windows(2)
+            .filter(|w| w[0] > 0.0)
+            .map(|w| w[1] / w[0] - 1.0)
+            .collect()
+    }
+
+    /// Historical Value-at-Risk: the return at the `1 - confidence` quantile of
+    /// `returns`, negative for a loss.
+    ///
+    /// `None` when `confidence` is outside (0, 1) or there are fewer than
+    /// `MIN_TAIL_OBSERVATIONS` returns to take a percentile of.
+    pub fn value_at_risk(returns: &[f64], confidence: f64) -> Option<f64> {
+        let sorted = Self::sorted_tail_sample(returns, confidence)?;
+        let rank = (1.0 - confidence) * (sorted.len() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+    }
+
+    /// Historical Expected Shortfall: the mean return at or below the Value-at-Risk,
+    /// negative for a loss. `None` under the same conditions as `value_at_risk`.
+    pub fn expected_shortfall(returns: &[f64], confidence: f64) -> Option<f64> {
+        let var = Self::value_at_risk(returns, confidence)?;
+        let tail: Vec<f64> = returns.iter().copied().filter(|&r| r <= var).collect();
+        if tail.is_empty() {
+            return Some(var);
         }
+        Some(tail.iter().sum::<f64>() / tail.len() as f64)
     }
 
-    pub fn calculate_volatility(equity_curve: &[(DateTime<Utc>, f64)], years: f64) -> f64 {
-        if equity_curve.len() < 2 || years <= 0.0 {
-            return 0.0;
+    fn sorted_tail_sample(returns: &[f64], confidence: f64) -> Option<Vec<f64>> {
+        if !(confidence > 0.0 && confidence < 1.0) {
+            return None;
         }
 
-        // Calculate daily returns
-        let mut returns = Vec::with_capacity(equity_curve.len() - 1);
-        for i in 1..equity_curve.len() {
-            let prev_equity = equity_curve[i - 1].1;
-            let curr_equity = equity_curve[i].1;
-            if prev_equity > 0.0 {
-                returns.push((curr_equity - prev_equity) / prev_equity);
-            }
+        let mut sorted: Vec<f64> = returns.iter().copied().filter(|r| r.is_finite()).collect();
+        if sorted.len() < MIN_TAIL_OBSERVATIONS {
+            return None;
         }
+        sorted.sort_by(f64::total_cmp);
+        Some(sorted)
+    }
+
+    /// Annualized standard deviation of per-bar returns, scaled by `periods_per_year`
+    /// bars per year (252 for daily data)
+    pub fn calculate_volatility(
+        equity_curve: &[(DateTime<Utc>, f64)],
+        periods_per_year: f64,
+        basis: ReturnBasis,
+    ) -> f64 {
+        if equity_curve.len() < 2 || periods_per_year <= 0.0 {
+            return 0.0;
+        }
+
+        // Calculate per-bar returns
+        let returns = Self::period_returns(equity_curve, basis);
 
         if returns.is_empty() {
             return 0.0;
@@ -72,8 +218,7 @@ impl Calculations {
             .sum::<f64>()
             / returns.len() as f64;
 
-        // Annualized volatility (assuming daily data)
-        variance.sqrt() * (252.0_f64).sqrt() // 252 trading days per year
+        variance.sqrt() * periods_per_year.sqrt()
     }
 
     pub fn calculate_drawdown_metrics(
@@ -141,32 +286,28 @@ impl Calculations {
         equity_curve: &[(DateTime<Utc>, f64)],
         return_ann: f64,
         risk_free_rate: f64,
+        periods_per_year: f64,
+        basis: ReturnBasis,
     ) -> f64 {
-        if equity_curve.len() < 2 {
+        if equity_curve.len() < 2 || periods_per_year <= 0.0 {
             return 0.0;
         }
 
-        // Calculate daily returns
-        let mut returns = Vec::with_capacity(equity_curve.len() - 1);
-        for i in 1..equity_curve.len() {
-            let prev_equity = equity_curve[i - 1].1;
-            let curr_equity = equity_curve[i].1;
-            if prev_equity > 0.0 {
-                returns.push((curr_equity - prev_equity) / prev_equity);
-            }
-        }
+        // Calculate per-bar returns
+        let returns = Self::period_returns(equity_curve, basis);
 
         if returns.is_empty() {
             return 0.0;
         }
 
         // Calculate downside deviation (only negative returns)
-        let daily_risk_free = risk_free_rate / 252.0; // Daily risk-free rate
+        // Per-bar risk-free rate
+        let period_risk_free = basis.excess_return(risk_free_rate, 0.0) / periods_per_year;
         let downside_returns: Vec<f64> = returns
             .iter()
             .filter_map(|&r| {
-                if r < daily_risk_free {
-                    Some(r - daily_risk_free)
+                if r < period_risk_free {
+                    Some(r - period_risk_free)
                 } else {
                     None
                 }
@@ -184,35 +325,334 @@ impl Calculations {
         let downside_variance =
             downside_returns.iter().map(|r| r.powi(2)).sum::<f64>() / downside_returns.len() as f64;
 
-        let downside_deviation = downside_variance.sqrt() * (252.0_f64).sqrt(); // Annualized
+        let downside_deviation = downside_variance.sqrt() * periods_per_year.sqrt(); // Annualized
 
         if downside_deviation > 0.0 {
-            (return_ann - risk_free_rate) / downside_deviation
+            basis.excess_return(return_ann, risk_free_rate) / downside_deviation
         } else {
             0.0
         }
     }
 
-    pub fn calculate_sqn(trades: &[Trade]) -> f64 {
-        if trades.len() < 2 {
-            return 0.0;
+    /// Number of date changes between consecutive timestamps
+    pub fn trading_days_spanned(timestamps: impl IntoIterator<Item = DateTime<Utc>>) -> usize {
+        let mut days = 0;
+        let mut previous = None;
+        for date in timestamps.into_iter().map(|timestamp| timestamp.date_naive()) {
+            if previous.is_some_and(|previous| previous != date) {
+                days += 1;
+            }
+            previous = Some(date);
         }
+        days
+    }
 
-        let avg_trade = trades.iter().map(|t| t.pl()).sum::<f64>() / trades.len() as f64;
+    /// Duration of a closed trade in the given unit (calendar durations are in days).
+    ///
+    /// `held_bars` are the timestamps of the bars from entry to exit, only needed for
+    /// trading-day durations.
+    pub fn trade_duration(
+        trade: &Trade,
+        unit: DurationUnit,
+        held_bars: impl IntoIterator<Item = DateTime<Utc>>,
+    ) -> Option<f64> {
+        match unit {
+            DurationUnit::Calendar => {
+                let exit_time = trade.exit_time?;
+                Some((exit_time - trade.entry_time).num_seconds() as f64 / 86_400.0)
+            }
+            DurationUnit::Bars => trade.duration_bars().map(|bars| bars as f64),
+            DurationUnit::TradingDays => {
+                trade.exit_bar?;
+                Some(Self::trading_days_spanned(held_bars) as f64)
+            }
+        }
+    }
+
+    /// Estimate the Hurst exponent of a price series using rescaled-range (R/S) analysis.
+    ///
+    /// The series is converted to log returns, split into non-overlapping chunks for a
+    /// range of lags (doubling from 8 up to half the series length), and the slope of
+    /// `ln(R/S)` against `ln(lag)` is returned. Values above 0.5 indicate a trending
+    /// series, below 0.5 a mean-reverting one, and around 0.5 a random walk.
+    ///
+    /// Returns 0.5 when there are too few prices to fit at least two lags.
+    pub fn hurst_exponent(prices: &[f64]) -> f64 {
+        let returns: Vec<f64> = prices
+            .windows(2)
+            .filter(|w| w[0] > 0.0 && w[1] > 0.0)
+            .map(|w| (w[1] / w[0]).ln())
+            .collect();
+
+        let mut points = Vec::new();
+        let mut lag = 8;
+        while lag <= returns.len() / 2 {
+            let rs_values: Vec<f64> = returns
+                .chunks_exact(lag)
+                .filter_map(Self::rescaled_range)
+                .collect();
+
+            if !rs_values.is_empty() {
+                let avg_rs = rs_values.iter().sum::<f64>() / rs_values.len() as f64;
+                points.push(((lag as f64).ln(), avg_rs.ln()));
+            }
+            lag *= 2;
+        }
+
+        if points.len() < 2 {
+            return 0.5;
+        }
 
-        // Calculate standard deviation of trade P&L
-        let variance = trades
+        // Least-squares slope of ln(R/S) on ln(lag)
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let covariance: f64 = points
             .iter()
-            .map(|t| (t.pl() - avg_trade).powi(2))
-            .sum::<f64>()
-            / trades.len() as f64;
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum();
+        let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+        if variance > 0.0 {
+            covariance / variance
+        } else {
+            0.5
+        }
+    }
+
+    fn rescaled_range(chunk: &[f64]) -> Option<f64> {
+        let mean = chunk.iter().sum::<f64>() / chunk.len() as f64;
+
+        let mut cumulative = 0.0;
+        let mut max_dev = f64::NEG_INFINITY;
+        let mut min_dev = f64::INFINITY;
+        for value in chunk {
+            cumulative += value - mean;
+            max_dev = max_dev.max(cumulative);
+            min_dev = min_dev.min(cumulative);
+        }
 
-        let std_dev = variance.sqrt();
+        let std_dev = (chunk.iter().map(|v| (v - mean).powi(2)).sum::<f64>()
+            / chunk.len() as f64)
+            .sqrt();
 
         if std_dev > 0.0 {
-            (trades.len() as f64).sqrt() * avg_trade / std_dev
+            Some((max_dev - min_dev) / std_dev)
         } else {
-            0.0
+            None
+        }
+    }
+
+    /// Pair each equity point with the benchmark close at the same timestamp,
+    /// dropping points the benchmark has no bar for
+    pub fn align_to_benchmark(
+        equity_curve: &[(DateTime<Utc>, f64)],
+        benchmark: &[OHLCV],
+    ) -> Vec<(DateTime<Utc>, f64, f64)> {
+        let closes: HashMap<DateTime<Utc>, f64> =
+            benchmark.iter().map(|bar| (bar.timestamp, bar.close)).collect();
+
+        equity_curve
+            .iter()
+            .filter_map(|&(timestamp, equity)| {
+                closes
+                    .get(&timestamp)
+                    .map(|&close| (timestamp, equity, close))
+            })
+            .collect()
+    }
+
+    /// Per-bar simple returns of the strategy and benchmark over the aligned points
+    pub fn paired_returns(
+        aligned: &[(DateTime<Utc>, f64, f64)],
+    ) -> Vec<(DateTime<Utc>, f64, f64)> {
+        aligned
+            .windows(2)
+            .filter(|w| w[0].1 > 0.0 && w[0].2 > 0.0)
+            .map(|w| {
+                (
+                    w[1].0,
+                    (w[1].1 - w[0].1) / w[0].1,
+                    (w[1].2 - w[0].2) / w[0].2,
+                )
+            })
+            .collect()
+    }
+
+    /// Beta of strategy returns on benchmark returns over each trailing window
+    pub fn rolling_beta(
+        returns: &[(DateTime<Utc>, f64, f64)],
+        window: usize,
+    ) -> Vec<(DateTime<Utc>, f64)> {
+        Self::rolling_moments(returns, window, |covariance, _, benchmark_var| {
+            (benchmark_var > 0.0).then(|| covariance / benchmark_var)
+        })
+    }
+
+    /// Pearson correlation of strategy and benchmark returns over each trailing window
+    pub fn rolling_correlation(
+        returns: &[(DateTime<Utc>, f64, f64)],
+        window: usize,
+    ) -> Vec<(DateTime<Utc>, f64)> {
+        Self::rolling_moments(returns, window, |covariance, strategy_var, benchmark_var| {
+            let denominator = (strategy_var * benchmark_var).sqrt();
+            (denominator > 0.0).then(|| covariance / denominator)
+        })
+    }
+
+    fn rolling_moments(
+        returns: &[(DateTime<Utc>, f64, f64)],
+        window: usize,
+        statistic: impl Fn(f64, f64, f64) -> Option<f64>,
+    ) -> Vec<(DateTime<Utc>, f64)> {
+        if window < 2 {
+            return Vec::new();
+        }
+
+        returns
+            .windows(window)
+            .filter_map(|w| {
+                let n = w.len() as f64;
+                let mean_s = w.iter().map(|(_, s, _)| s).sum::<f64>() / n;
+                let mean_b = w.iter().map(|(_, _, b)| b).sum::<f64>() / n;
+
+                let mut covariance = 0.0;
+                let mut strategy_var = 0.0;
+                let mut benchmark_var = 0.0;
+                for (_, s, b) in w {
+                    covariance += (s - mean_s) * (b - mean_b);
+                    strategy_var += (s - mean_s).powi(2);
+                    benchmark_var += (b - mean_b).powi(2);
+                }
+
+                statistic(covariance, strategy_var, benchmark_var)
+                    .map(|value| (w[w.len() - 1].0, value))
+            })
+            .collect()
+    }
+
+    /// Volume traded at each price level, spreading each bar's volume over its range.
+    ///
+    /// See `volume_profile_with` for the binning rules.
+    pub fn volume_profile(data: &[OHLCV], bins: usize) -> Vec<(f64, f64)> {
+        Self::volume_profile_with(data, bins, VolumeDistribution::Range)
+    }
+
+    /// Bucket traded volume into `bins` equal-width price bins spanning the lowest low
+    /// to the highest high, returned as `(bin_midpoint, volume)` from low to high.
+    ///
+    /// With `VolumeDistribution::Range` a bar's volume is shared between the bins its
+    /// high-low range overlaps in proportion to the overlap; bars with no range fall
+    /// back to the bin containing the close.
+    pub fn volume_profile_with(
+        data: &[OHLCV],
+        bins: usize,
+        distribution: VolumeDistribution,
+    ) -> Vec<(f64, f64)> {
+        if data.is_empty() || bins == 0 {
+            return Vec::new();
+        }
+
+        let min_price = data.iter().map(|bar| bar.low).fold(f64::INFINITY, f64::min);
+        let max_price = data.iter().map(|bar| bar.high).fold(f64::NEG_INFINITY, f64::max);
+        let width = (max_price - min_price) / bins as f64;
+
+        let mut volumes = vec![0.0; bins];
+        let bin_of = |price: f64| -> usize {
+            if width > 0.0 {
+                (((price - min_price) / width) as usize).min(bins - 1)
+            } else {
+                0
+            }
+        };
+
+        for bar in data {
+            let range = bar.high - bar.low;
+            if distribution == VolumeDistribution::Close || range <= 0.0 || width <= 0.0 {
+                volumes[bin_of(bar.close)] += bar.volume;
+                continue;
+            }
+
+            let (first, last) = (bin_of(bar.low), bin_of(bar.high));
+            for (bin, volume) in volumes.iter_mut().enumerate().take(last + 1).skip(first) {
+                let bin_low = min_price + bin as f64 * width;
+                let overlap = bar.high.min(bin_low + width) - bar.low.max(bin_low);
+                if overlap > 0.0 {
+                    *volume += bar.volume * overlap / range;
+                }
+            }
         }
+
+        volumes
+            .into_iter()
+            .enumerate()
+            .map(|(bin, volume)| (min_price + (bin as f64 + 0.5) * width, volume))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+
+    /// Prices whose log returns follow an AR(1) process with coefficient `phi`
+    fn ar1_prices(phi: f64) -> Vec<f64> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let mut prices = vec![100.0];
+        let mut ret = 0.0;
+        for _ in 0..2048 {
+            ret = phi * ret + (rng.gen::<f64>() - 0.5) * 0.02;
+            prices.push(prices.last().unwrap() * f64::exp(ret));
+        }
+        prices
+    }
+
+    #[test]
+    fn hurst_exponent_is_above_half_for_a_trending_series() {
+        let hurst = Calculations::hurst_exponent(&ar1_prices(0.8));
+        assert!(hurst > 0.5, "{hurst}");
+    }
+
+    #[test]
+    fn hurst_exponent_is_below_half_for_a_mean_reverting_series() {
+        let hurst = Calculations::hurst_exponent(&ar1_prices(-0.8));
+        assert!(hurst < 0.5, "{hurst}");
+    }
+
+    #[test]
+    fn log_and_simple_returns_give_close_but_distinct_volatility() {
+        let start = DateTime::<Utc>::UNIX_EPOCH;
+        let curve: Vec<_> = ar1_prices(0.0)
+            .into_iter()
+            .enumerate()
+            .map(|(i, price)| (start + chrono::Duration::days(i as i64), price))
+            .collect();
+
+        let simple = Calculations::calculate_volatility(&curve, 252.0, ReturnBasis::Simple);
+        let log = Calculations::calculate_volatility(&curve, 252.0, ReturnBasis::Log);
+        assert_ne!(simple, log);
+        assert!((simple / log - 1.0).abs() < 0.01, "{simple} vs {log}");
+    }
+
+    #[test]
+    fn a_cluster_of_bars_makes_a_volume_peak_at_its_price() {
+        // Two bars span 90 to 110 and five trade between 104 and 105, all 1000 volume
+        let mut prices = vec![(100.0, 110.0, 90.0, 100.0); 2];
+        prices.extend([(104.5, 105.0, 104.0, 104.5); 5]);
+        let data = crate::test_util::bars(&prices);
+
+        let profile = Calculations::volume_profile(&data, 10);
+        assert_eq!(profile.len(), 10);
+        let (peak_price, peak_volume) = profile
+            .iter()
+            .copied()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        // The 104-106 bin holds the cluster plus a tenth of each wide bar
+        assert!((peak_price - 105.0).abs() < 1e-9, "{peak_price}");
+        assert!((peak_volume - 5_200.0).abs() < 1e-9, "{peak_volume}");
+        let total: f64 = profile.iter().map(|&(_, volume)| volume).sum();
+        assert!((total - 7_000.0).abs() < 1e-9);
     }
-}
\ No newline at end of file
+}
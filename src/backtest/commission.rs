@@ -0,0 +1,69 @@
+use std::fmt::Debug;
+
+/// Fee charged by the broker for a single fill
+pub trait CommissionModel: Debug + Send + Sync {
+    /// Commission for filling `size` units at `price`
+    fn commission(&self, size: f64, price: f64) -> f64;
+}
+
+/// Fraction of the traded notional, e.g. `0.001` for 0.1%
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Percentage {
+    pub rate: f64,
+}
+
+impl CommissionModel for Percentage {
+    fn commission(&self, size: f64, price: f64) -> f64 {
+        size.abs() * price * self.rate
+    }
+}
+
+/// Fixed fee per unit traded, with a minimum charge per fill
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PerShare {
+    pub per_share: f64,
+    pub minimum: f64,
+}
+
+impl CommissionModel for PerShare {
+    fn commission(&self, size: f64, _price: f64) -> f64 {
+        (size.abs() * self.per_share).max(self.minimum)
+    }
+}
+
+/// Flat fee for every fill regardless of its size
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedPerTrade {
+    pub fee: f64,
+}
+
+impl CommissionModel for FixedPerTrade {
+    fn commission(&self, _size: f64, _price: f64) -> f64 {
+        self.fee
+    }
+}
+
+/// Percentage rate that depends on the fill's notional, with a minimum charge.
+///
+/// `tiers` holds `(notional_threshold, rate)` pairs; the rate of the highest threshold
+/// at or below the notional applies, and fills below every threshold use the first rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tiered {
+    pub tiers: Vec<(f64, f64)>,
+    pub minimum: f64,
+}
+
+impl CommissionModel for Tiered {
+    fn commission(&self, size: f64, price: f64) -> f64 {
+        let notional = size.abs() * price;
+        let rate = self
+            .tiers
+            .iter()
+            .filter(|(threshold, _)| *threshold <= notional)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .or_else(|| self.tiers.first())
+            .map_or(0.0, |(_, rate)| *rate);
+
+        (notional * rate).max(self.minimum)
+    }
+}
\ No newline at end of file
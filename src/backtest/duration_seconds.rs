@@ -0,0 +1,15 @@
+//! Serde helpers that store a `chrono::Duration` as a number of seconds
+use chrono::Duration;
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    let seconds = duration.num_seconds() as f64 + f64::from(duration.subsec_nanos()) * 1e-9;
+    serializer.serialize_f64(seconds)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let seconds = f64::deserialize(deserializer)?;
+    let whole = seconds.trunc();
+    let nanos = ((seconds - whole) * 1e9).round();
+    Ok(Duration::seconds(whole as i64) + Duration::nanoseconds(nanos as i64))
+}
@@ -0,0 +1,78 @@
+use crate::types::OHLCV;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Assumed order in which a bar visited its prices, used to decide which of two
+/// levels inside the bar's range was touched first.
+///
+/// Bars don't record the order, so every choice is an assumption about data the
+/// engine doesn't have. Only `Conservative` can't flatter the results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum IntrabarPath {
+    /// The move against the position comes first, so a stop loss fills before a take
+    /// profit on the same bar
+    #[default]
+    Conservative,
+    /// The move in the position's favour comes first, so a take profit fills before a
+    /// stop loss on the same bar. An upper bound: it overstates results whenever a
+    /// bar hits both levels.
+    Optimistic,
+    /// Open, high, low, close: the high is reached before the low
+    OHLC,
+    /// Open, low, high, close: the low is reached before the high
+    OLHC,
+    /// High-first or low-first chosen per bar by a generator seeded with the value,
+    /// so runs are reproducible
+    Random(u64),
+}
+
+impl IntrabarPath {
+    /// Whether the bar at `bar_index` is assumed to reach its high before its low
+    /// while a long (`is_long`) or short position is open
+    pub fn high_first(&self, bar_index: usize, is_long: bool) -> bool {
+        match *self {
+            IntrabarPath::Conservative => !is_long,
+            IntrabarPath::Optimistic => is_long,
+            IntrabarPath::OHLC => true,
+            IntrabarPath::OLHC => false,
+            IntrabarPath::Random(seed) => {
+                let bar_seed = seed ^ (bar_index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                StdRng::seed_from_u64(bar_seed).gen_bool(0.5)
+            }
+        }
+    }
+
+    /// Fill price for the first of `above` (a level over the open) and `below` (a level
+    /// under the open) that the bar touches, if any.
+    ///
+    /// A bar that opens through a level fills at the open rather than the level; when the
+    /// range spans both levels the path decides which came first.
+    pub fn first_touched(
+        &self,
+        bar: &OHLCV,
+        bar_index: usize,
+        is_long: bool,
+        above: Option<f64>,
+        below: Option<f64>,
+    ) -> Option<f64> {
+        let gapped_above = above.is_some_and(|level| bar.open >= level);
+        let gapped_below = below.is_some_and(|level| bar.open <= level);
+        if gapped_above || gapped_below {
+            return Some(bar.open);
+        }
+
+        let hit_above = above.filter(|&level| bar.high >= level);
+        let hit_below = below.filter(|&level| bar.low <= level);
+        match (hit_above, hit_below) {
+            (Some(high_level), Some(low_level)) => {
+                if self.high_first(bar_index, is_long) {
+                    Some(high_level)
+                } else {
+                    Some(low_level)
+                }
+            }
+            (hit_above, hit_below) => hit_above.or(hit_below),
+        }
+    }
+}
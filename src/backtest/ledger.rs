@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Kind of cash-affecting event recorded in the ledger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LedgerEventType {
+    /// Cash paid for a long purchase, or the P&L settled when covering a short
+    Buy,
+    /// Proceeds from selling a long position
+    Sell,
+    Commission,
+    PerformanceFee,
+    Financing,
+    Interest,
+}
+
+/// A single cash movement with the resulting account balances
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub timestamp: DateTime<Utc>,
+    pub event: LedgerEventType,
+    /// Signed change in cash (negative for debits)
+    pub amount: f64,
+    /// Cash balance after the event
+    pub cash: f64,
+    /// Account equity after the event
+    pub equity: f64,
+}
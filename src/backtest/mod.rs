@@ -1,23 +1,111 @@
-use crate::order::{Order, OrderSide};
-use crate::position::Position;
-use crate::strategy::Strategy;
+use crate::error::BacktestError;
+use crate::order::{Order, OrderSide, OrderStatus, OrderType, SizeUnit};
+use crate::position::{LotMatching, Position};
+use crate::strategy::{Context, Strategy};
 use crate::trade::Trade;
-use crate::types::OHLCV;
+use crate::types::{CalendarPeriod, OHLCV};
 use crate::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 
-mod calculations;
-use calculations::Calculations;
+pub(crate) mod accounting;
+mod builder;
+pub mod calculations;
+mod commission;
+mod duration_seconds;
+mod intrabar;
+mod ledger;
+mod slippage;
+mod tag_stats;
+#[cfg(test)]
+mod tests;
+mod trade_sink;
+mod trade_stats;
+mod trade_summary;
+use accounting::{BuyingPower, Fill};
+pub use builder::BacktestConfigBuilder;
+pub use calculations::{Calculations, VolumeDistribution};
+pub use commission::{CommissionModel, FixedPerTrade, PerShare, Percentage, Tiered};
+pub use intrabar::IntrabarPath;
+pub use ledger::{LedgerEntry, LedgerEventType};
+pub use slippage::{
+    FixedBps, FixedTicks, NoSlippage, SlippageModel, SquareRootImpact, VolumeProportional,
+};
+pub use tag_stats::{TagStats, UNTAGGED};
+pub use trade_sink::TradeSink;
+pub use trade_stats::TradeStats;
+use trade_summary::TradeSummary;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BacktestConfig {
     pub initial_cash: f64,
+    /// Commission as a fraction of notional, used when `commission_model` is not set
     pub commission: f64,
+    /// Broker fee schedule applied to every fill instead of the flat `commission` rate
+    #[serde(skip)]
+    pub commission_model: Option<Arc<dyn CommissionModel>>,
+    /// Initial margin as a fraction of position value: buying power is `equity / margin`,
+    /// so `0.25` allows 4:1 leverage and `1.0` none
     pub margin: f64,
+    /// Equity required to keep a position open, as a fraction of its market value.
+    /// Below it the position is liquidated at the bar's close; `0.0` liquidates only
+    /// once equity turns negative.
+    pub maintenance_margin: f64,
+    /// Fill market orders at the next bar's open, as `FillTiming::NextBarOpen` does,
+    /// instead of at the close of the bar that produced them
     pub trade_on_open: bool,
+    /// Keep every fill as its own position so longs and shorts can be held at once;
+    /// when false, opposite fills net against the open position
     pub hedging: bool,
+    /// Each new order cancels the resting ones and closes every open position before
+    /// it fills, so only the latest order is ever in effect; when false, orders stack
+    /// and average into the open position
     pub exclusive_orders: bool,
+    /// Incentive fee charged on equity above the high-water mark
+    pub performance_fee: Option<PerformanceFee>,
+    /// Annual financing rate charged on borrowed funds and short notional
+    pub financing_rate: f64,
+    /// Annual interest rate credited on idle (uninvested) cash
+    pub cash_interest_rate: f64,
+    /// Adjustment applied to every fill price
+    #[serde(skip, default = "no_slippage")]
+    pub slippage: Arc<dyn SlippageModel>,
+    /// Broker fee schedules for specific symbols, overriding `commission_model` and
+    /// `commission`
+    #[serde(skip)]
+    pub commission_overrides: HashMap<String, Arc<dyn CommissionModel>>,
+    /// Slippage models for specific symbols, overriding `slippage`
+    #[serde(skip)]
+    pub slippage_overrides: HashMap<String, Arc<dyn SlippageModel>>,
+    /// What the strategy sees when it decides and when its orders fill
+    pub fill_timing: FillTiming,
+    /// Assumed price path inside a bar when checking stop-loss and take-profit levels
+    pub intrabar_path: IntrabarPath,
+    /// Unit used for the `*_trade_duration_units` results
+    pub duration_unit: DurationUnit,
+    /// Per-bar return definition used for volatility, Sharpe and Sortino
+    pub return_basis: ReturnBasis,
+    /// Annual risk-free rate subtracted from returns in the Sharpe and Sortino ratios
+    pub risk_free_rate: f64,
+    /// Bars per year used to annualize volatility, Sharpe and Sortino, e.g. 252 for
+    /// daily stock data or 52 for weekly bars. `None` infers it from the bar timestamps
+    /// with `Calculations::infer_periods_per_year`.
+    pub periods_per_year: Option<f64>,
+    /// Stream closed trades here instead of retaining them in the results
+    #[serde(skip)]
+    pub trade_sink: Option<TradeSink>,
+    /// Deduct one round trip of commission from the buy-and-hold benchmark
+    pub buy_hold_net_of_commission: bool,
+    /// Which entries a reducing fill closes first when a position was built up over
+    /// several fills
+    pub lot_matching: LotMatching,
+    /// What happens to an order larger than the available buying power
+    pub partial_fills: PartialFills,
+    /// Allow fractional units; when false every fill is rounded down to whole units,
+    /// as for shares that can't be split
+    pub fractional: bool,
 }
 
 impl Default for BacktestConfig {
@@ -25,20 +113,217 @@ impl Default for BacktestConfig {
         Self {
             initial_cash: 10000.0,
             commission: 0.0,
+            commission_model: None,
             margin: 1.0,
+            maintenance_margin: 0.0,
             trade_on_open: false,
             hedging: false,
-            exclusive_orders: true,
+            exclusive_orders: false,
+            performance_fee: None,
+            financing_rate: 0.0,
+            cash_interest_rate: 0.0,
+            slippage: no_slippage(),
+            commission_overrides: HashMap::new(),
+            slippage_overrides: HashMap::new(),
+            fill_timing: FillTiming::Standard,
+            intrabar_path: IntrabarPath::Conservative,
+            duration_unit: DurationUnit::Calendar,
+            return_basis: ReturnBasis::Simple,
+            risk_free_rate: 0.02,
+            periods_per_year: None,
+            trade_sink: None,
+            buy_hold_net_of_commission: false,
+            lot_matching: LotMatching::Fifo,
+            partial_fills: PartialFills::Reject,
+            fractional: true,
         }
     }
 }
 
+impl BacktestConfig {
+    /// Start a `BacktestConfigBuilder` from the default settings
+    pub fn builder() -> BacktestConfigBuilder {
+        BacktestConfigBuilder::new()
+    }
+
+    /// Whether market orders fill at a bar's open rather than its close
+    pub fn fills_on_open(&self) -> bool {
+        self.trade_on_open || self.fill_timing != FillTiming::Standard
+    }
+
+    /// Whether market orders wait for the open of the bar after the one that produced
+    /// them, so they never fill at a price the strategy saw before deciding
+    pub fn defers_market_orders(&self) -> bool {
+        match self.fill_timing {
+            FillTiming::Standard => self.trade_on_open,
+            FillTiming::SignalAndFillOnOpen => false,
+            FillTiming::NextBarOpen => true,
+        }
+    }
+
+    /// `units` rounded down to a whole number unless `fractional` is set
+    pub fn tradable_units(&self, units: f64) -> f64 {
+        if self.fractional {
+            units
+        } else {
+            units.floor()
+        }
+    }
+
+    /// Commission for filling `size` units of `symbol` at `price`.
+    ///
+    /// A per-symbol override model wins, then the configured `commission_model`, then
+    /// the flat `commission` rate.
+    pub fn commission_for(&self, symbol: Option<&str>, size: f64, price: f64) -> f64 {
+        if let Some(model) = symbol.and_then(|s| self.commission_overrides.get(s)) {
+            return model.commission(size, price);
+        }
+
+        match self.commission_model {
+            Some(ref model) => model.commission(size, price),
+            None => Percentage {
+                rate: self.commission,
+            }
+            .commission(size, price),
+        }
+    }
+
+    /// Slippage model for an order on `symbol`, falling back to the global model
+    pub fn slippage_for(&self, symbol: Option<&str>) -> &dyn SlippageModel {
+        symbol
+            .and_then(|s| self.slippage_overrides.get(s))
+            .unwrap_or(&self.slippage)
+            .as_ref()
+    }
+}
+
+fn no_slippage() -> Arc<dyn SlippageModel> {
+    Arc::new(NoSlippage)
+}
+
+/// When strategy decisions are made relative to the bar and where market orders fill
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FillTiming {
+    /// The strategy sees the complete bar and its market orders fill at that bar's
+    /// close, or at the next bar's open when `trade_on_open` is set.
+    ///
+    /// Filling at the close peeks ahead: it assumes the order went in once the close
+    /// was known but before it printed.
+    Standard,
+    /// The strategy sees only the bar's open (high, low, close and volume are NaN)
+    /// and its orders fill at that open, for strategies that trade the opening print.
+    /// No look-ahead.
+    SignalAndFillOnOpen,
+    /// The strategy sees the complete bar and its market orders fill at the next bar's
+    /// open, as a live order placed after the close would. No look-ahead; orders from
+    /// the last bar never fill.
+    NextBarOpen,
+}
+
+/// How an order larger than the available buying power is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PartialFills {
+    /// Drop the whole order
+    Reject,
+    /// Fill as many units as the buying power allows and cancel the rest
+    CancelRemainder,
+    /// Fill as many units as the buying power allows and keep the rest resting, to
+    /// fill on later bars as buying power frees up. A resting market order fills at
+    /// the next bar's open.
+    RestRemainder,
+}
+
+/// How trade durations are measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DurationUnit {
+    /// Wall-clock days, including weekends and holidays
+    Calendar,
+    /// Number of bars between entry and exit
+    Bars,
+    /// Number of distinct trading dates crossed between entry and exit
+    TradingDays,
+}
+
+/// How per-bar returns are computed from consecutive equity values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReturnBasis {
+    /// `(curr - prev) / prev`
+    Simple,
+    /// `ln(curr / prev)`, which adds up across bars
+    Log,
+}
+
+impl ReturnBasis {
+    pub fn period_return(&self, prev: f64, curr: f64) -> f64 {
+        match self {
+            ReturnBasis::Simple => (curr - prev) / prev,
+            ReturnBasis::Log => (curr / prev).ln(),
+        }
+    }
+
+    /// Annualized excess return over `risk_free_rate`, both given as simple rates
+    pub fn excess_return(&self, return_ann: f64, risk_free_rate: f64) -> f64 {
+        match self {
+            ReturnBasis::Simple => return_ann - risk_free_rate,
+            ReturnBasis::Log => (1.0 + return_ann).ln() - (1.0 + risk_free_rate).ln(),
+        }
+    }
+}
+
+/// Performance fee assessed at the end of each calendar period.
+///
+/// When equity closes a period above the high-water mark, `rate` of the gain over
+/// the mark is deducted from cash and the mark is reset to the post-fee equity.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PerformanceFee {
+    pub rate: f64,
+    pub period: CalendarPeriod,
+}
+
+/// Serializable snapshot of the engine state between bars, see `Backtest::checkpoint`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestCheckpoint {
+    positions: Vec<Position>,
+    cash: f64,
+    equity_curve: Vec<(DateTime<Utc>, f64)>,
+    trades: Vec<Trade>,
+    trade_summary: TradeSummary,
+    current_bar_index: usize,
+    next_bar: usize,
+    high_water_mark: f64,
+    /// Bars during which at least one position was open
+    exposed_bars: usize,
+    /// Price the buy-and-hold benchmark enters at, from the first bar
+    buy_hold_entry: Option<f64>,
+    last_close: f64,
+    context: Context,
+    awaiting_confirmation: Vec<(Order, usize)>,
+    pending_orders: Vec<Order>,
+    ledger: Vec<LedgerEntry>,
+    indicator_series: HashMap<String, RecordedSeries>,
+    #[serde(default)]
+    next_position_id: usize,
+}
+
+impl BacktestCheckpoint {
+    /// Number of bars processed when the checkpoint was taken
+    pub fn bars_processed(&self) -> usize {
+        self.next_bar
+    }
+}
+
+/// Per-bar values recorded by a strategy, `None` where nothing was recorded
+pub type RecordedSeries = Vec<(DateTime<Utc>, Option<f64>)>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BacktestResults {
     pub start_date: DateTime<Utc>,
     pub end_date: DateTime<Utc>,
+    #[serde(with = "duration_seconds")]
     pub duration: chrono::Duration,
     pub exposure_time: f64,
+    /// Cash the backtest started with
+    pub initial_equity: f64,
     pub equity_final: f64,
     pub equity_peak: f64,
     pub return_pct: f64,
@@ -50,211 +335,1178 @@ pub struct BacktestResults {
     pub calmar_ratio: f64,
     pub max_drawdown: f64,
     pub avg_drawdown: f64,
+    #[serde(with = "duration_seconds")]
     pub max_drawdown_duration: chrono::Duration,
+    #[serde(with = "duration_seconds")]
     pub avg_drawdown_duration: chrono::Duration,
+    /// Historical 95% one-day Value-at-Risk, see `value_at_risk`
+    pub value_at_risk_95: Option<f64>,
+    /// Closed trades, empty when they were streamed to `BacktestConfig::trade_sink`
     pub trades: Vec<Trade>,
+    /// Number of closed trades, including any streamed to a sink
+    pub trade_count: usize,
     pub win_rate: f64,
-    pub best_trade: f64,
-    pub worst_trade: f64,
-    pub avg_trade: f64,
+    /// P&L of the best closed trade, `None` when no trade closed
+    pub best_trade: Option<f64>,
+    /// P&L of the worst closed trade, `None` when no trade closed
+    pub worst_trade: Option<f64>,
+    /// Mean P&L per closed trade, `None` when no trade closed
+    pub avg_trade: Option<f64>,
+    /// Commission paid on every fill, including positions still open
+    pub total_commissions: f64,
+    /// Win/loss breakdown, streaks and per-trade returns
+    pub trade_stats: TradeStats,
+    #[serde(with = "duration_seconds")]
     pub max_trade_duration: chrono::Duration,
+    #[serde(with = "duration_seconds")]
     pub avg_trade_duration: chrono::Duration,
+    /// Longest trade measured in `BacktestConfig::duration_unit`
+    pub max_trade_duration_units: f64,
+    /// Average trade measured in `BacktestConfig::duration_unit`
+    pub avg_trade_duration_units: f64,
     pub profit_factor: f64,
-    pub expectancy: f64,
+    /// Expected P&L per trade, `None` when no trade closed
+    pub expectancy: Option<f64>,
     pub sqn: f64, // System Quality Number
+    /// R-multiples of the retained trades that had a stop loss, in trade order; empty
+    /// when trades stream to a `trade_sink`
+    pub r_multiples: Vec<f64>,
+    /// Mean R-multiple, `None` when no trade had a stop loss
+    pub avg_r: Option<f64>,
+    /// Win rate times average winning R minus loss rate times average losing R
+    pub expectancy_r: Option<f64>,
+    /// Chronological record of every cash movement
+    pub ledger: Vec<LedgerEntry>,
+    /// Indicator values recorded by the strategy, aligned to the bars
+    pub indicator_series: HashMap<String, RecordedSeries>,
+    /// Mark-to-market equity at the close of every bar
+    pub equity_curve: Vec<(DateTime<Utc>, f64)>,
+}
+
+impl BacktestResults {
+    /// Results as pretty-printed JSON, with durations in seconds
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Write the closed trades to a CSV file, one row per trade.
+    ///
+    /// Only the header is written when trades were streamed to a `TradeSink`.
+    pub fn write_csv<P: AsRef<std::path::Path>>(&self, path: P) -> Result<()> {
+        let mut writer = csv::Writer::from_path(path)?;
+        writer.write_record([
+            "entry_bar",
+            "exit_bar",
+            "entry_time",
+            "exit_time",
+            "entry_price",
+            "exit_price",
+            "size",
+            "pl",
+            "pl_pct",
+            "commission",
+            "tag",
+        ])?;
+
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        for trade in &self.trades {
+            writer.write_record([
+                trade.entry_bar.to_string(),
+                optional(trade.exit_bar.map(|bar| bar.to_string())),
+                trade.entry_time.to_rfc3339(),
+                optional(trade.exit_time.map(|time| time.to_rfc3339())),
+                trade.entry_price.to_string(),
+                optional(trade.exit_price.map(|price| price.to_string())),
+                trade.size.to_string(),
+                trade.pl().to_string(),
+                trade.pl_pct().to_string(),
+                trade.commissions().to_string(),
+                optional(trade.tag.clone()),
+            ])?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Historical one-day Value-at-Risk at `confidence` (e.g. 0.95): the daily return
+    /// of the equity curve that is only undercut on `1 - confidence` of days, negative
+    /// for a loss. `None` with fewer than 20 daily returns.
+    pub fn value_at_risk(&self, confidence: f64) -> Option<f64> {
+        Calculations::value_at_risk(&Calculations::daily_returns(&self.equity_curve), confidence)
+    }
+
+    /// Historical one-day Expected Shortfall at `confidence`: the mean daily return on
+    /// the days at or beyond the Value-at-Risk, negative for a loss
+    pub fn expected_shortfall(&self, confidence: f64) -> Option<f64> {
+        let returns = Calculations::daily_returns(&self.equity_curve);
+        Calculations::expected_shortfall(&returns, confidence)
+    }
+
+    /// Closed trades whose entry order was tagged `tag`
+    pub fn trades_with_tag(&self, tag: &str) -> Vec<&Trade> {
+        self.trades
+            .iter()
+            .filter(|trade| trade.tag.as_deref() == Some(tag))
+            .collect()
+    }
+
+    /// Trade count, win rate, net P&L and profit factor of the closed trades grouped by
+    /// entry tag, with untagged trades under `UNTAGGED`
+    pub fn performance_by_tag(&self) -> HashMap<String, TagStats> {
+        let mut summaries: HashMap<String, TradeSummary> = HashMap::new();
+        for trade in &self.trades {
+            let tag = trade.tag.as_deref().unwrap_or(UNTAGGED);
+            summaries
+                .entry(tag.to_string())
+                .or_default()
+                .record(trade, None);
+        }
+
+        summaries
+            .into_iter()
+            .map(|(tag, summary)| {
+                let stats = TagStats {
+                    trade_count: summary.count,
+                    win_rate: summary.win_rate(),
+                    total_pl: summary.total_pl,
+                    profit_factor: summary.profit_factor(),
+                };
+                (tag, stats)
+            })
+            .collect()
+    }
+
+    /// Drawdown from the running equity peak at every bar, as a negative fraction,
+    /// for underwater plots
+    pub fn drawdown_series(&self) -> Vec<(DateTime<Utc>, f64)> {
+        Calculations::drawdown_series(&self.equity_curve)
+    }
+
+    /// Return of every calendar month with bars, keyed by the first of the month
+    pub fn monthly_returns(&self) -> Vec<(NaiveDate, f64)> {
+        Calculations::calendar_returns(
+            &self.equity_curve,
+            self.initial_equity,
+            CalendarPeriod::Month,
+        )
+    }
+
+    /// Return of every calendar year with bars, keyed by January 1st
+    pub fn yearly_returns(&self) -> Vec<(NaiveDate, f64)> {
+        Calculations::calendar_returns(
+            &self.equity_curve,
+            self.initial_equity,
+            CalendarPeriod::Year,
+        )
+    }
+
+    /// Beta of the strategy's per-bar returns against `benchmark` over a sliding window.
+    ///
+    /// Bars are matched by timestamp; each point is stamped with the last bar of its window.
+    pub fn rolling_beta(&self, benchmark: &[OHLCV], window: usize) -> Vec<(DateTime<Utc>, f64)> {
+        let aligned = Calculations::align_to_benchmark(&self.equity_curve, benchmark);
+        Calculations::rolling_beta(&Calculations::paired_returns(&aligned), window)
+    }
+
+    /// Correlation of the strategy's per-bar returns with `benchmark` over a sliding window
+    pub fn rolling_correlation(
+        &self,
+        benchmark: &[OHLCV],
+        window: usize,
+    ) -> Vec<(DateTime<Utc>, f64)> {
+        let aligned = Calculations::align_to_benchmark(&self.equity_curve, benchmark);
+        Calculations::rolling_correlation(&Calculations::paired_returns(&aligned), window)
+    }
+
+    /// Maximum drawdown of the strategy's equity relative to `benchmark`, as a negative fraction.
+    ///
+    /// Both series are normalized to 1.0 at the first shared timestamp, so this is the
+    /// worst stretch of underperformance regardless of the absolute return.
+    pub fn tracking_drawdown(&self, benchmark: &[OHLCV]) -> f64 {
+        let aligned = Calculations::align_to_benchmark(&self.equity_curve, benchmark);
+        let Some(&(_, base_equity, base_close)) = aligned.first() else {
+            return 0.0;
+        };
+        if base_equity <= 0.0 || base_close <= 0.0 {
+            return 0.0;
+        }
+
+        let relative_curve: Vec<(DateTime<Utc>, f64)> = aligned
+            .iter()
+            .map(|&(timestamp, equity, close)| {
+                (timestamp, (equity / base_equity) / (close / base_close))
+            })
+            .collect();
+
+        Calculations::calculate_drawdown_metrics(&relative_curve).0
+    }
 }
 
 pub struct Backtest<'a> {
     config: BacktestConfig,
     data: &'a [OHLCV],
-    current_position: Option<Position>,
+    positions: Vec<Position>,
     cash: f64,
     equity_curve: Vec<(DateTime<Utc>, f64)>,
     trades: Vec<Trade>,
+    trade_summary: TradeSummary,
     current_bar_index: usize,
-    position_entry_bar: Option<usize>,
+    next_bar: usize,
+    high_water_mark: f64,
+    /// Bars during which at least one position was open
+    exposed_bars: usize,
+    /// Price the buy-and-hold benchmark enters at, from the first bar
+    buy_hold_entry: Option<f64>,
+    last_close: f64,
+    context: Context,
+    awaiting_confirmation: Vec<(Order, usize)>,
+    pending_orders: Vec<Order>,
+    ledger: Vec<LedgerEntry>,
+    indicator_series: HashMap<String, RecordedSeries>,
+    /// Trades closed since the strategy was last told, always empty between bars
+    unreported_trades: Vec<Trade>,
+    /// Id the next opened position gets
+    next_position_id: usize,
 }
 
 impl<'a> Backtest<'a> {
     pub fn new(data: &'a [OHLCV], config: BacktestConfig) -> Self {
         let cash = config.initial_cash;
-        
+
         let mut equity_curve = Vec::new();
         let mut trades = Vec::new();
-        
+
         equity_curve.reserve(data.len());
         trades.reserve(data.len() / 10);
-        
+
         Self {
             config,
             data,
-            current_position: None,
+            positions: Vec::new(),
             cash,
             equity_curve,
             trades,
+            trade_summary: TradeSummary::default(),
             current_bar_index: 0,
-            position_entry_bar: None,
+            next_bar: 0,
+            high_water_mark: cash,
+            exposed_bars: 0,
+            buy_hold_entry: None,
+            last_close: 0.0,
+            context: Context::new(),
+            awaiting_confirmation: Vec::new(),
+            pending_orders: Vec::new(),
+            ledger: Vec::new(),
+            indicator_series: HashMap::new(),
+            unreported_trades: Vec::new(),
+            next_position_id: 0,
         }
     }
 
-    pub fn run<S: Strategy>(&mut self, mut strategy: S) -> Result<BacktestResults> {
+    /// Run `strategy` over every bar and return the results. The engine is consumed so
+    /// its trades and series move into the results instead of being copied.
+    pub fn run<S: Strategy>(mut self, mut strategy: S) -> Result<BacktestResults> {
+        self.start(&mut strategy)?;
+        while self.step(&mut strategy)? {}
+
+        let results = self.into_results()?;
+        strategy.on_finish(&results)?;
+        Ok(results)
+    }
+
+    /// Check the data is long enough for `strategy` and initialize it.
+    ///
+    /// Call once before stepping, including when resuming from a checkpoint.
+    pub fn start<S: Strategy>(&mut self, strategy: &mut S) -> Result<()> {
+        let need = strategy.min_bars();
+        if self.data.len() < need {
+            return Err(BacktestError::InsufficientData {
+                have: self.data.len(),
+                need,
+            }
+            .into());
+        }
+
         // Initialize strategy
-        strategy.init(self.data)?;
+        strategy.init(self.data)
+    }
+
+    /// Run `strategy` over bars pulled one at a time from `bars` rather than the
+    /// engine's data slice, so the data set never has to be held in memory or can come
+    /// from a live or replay feed.
+    ///
+    /// The strategy's `init` receives an empty slice, so it has to build its indicators
+    /// incrementally in `next`. Only the equity curve and the results are accumulated;
+    /// set a `trade_sink` to stream the trades out as well.
+    pub fn run_streaming<I, S>(mut self, bars: I, mut strategy: S) -> Result<BacktestResults>
+    where
+        I: IntoIterator<Item = OHLCV>,
+        S: Strategy,
+    {
+        strategy.init(&[])?;
+
+        let mut bars = bars.into_iter().peekable();
+        while let Some(bar) = bars.next() {
+            let next_timestamp = bars.peek().map(|next| next.timestamp);
+            self.process_bar(&mut strategy, &bar, next_timestamp)?;
+        }
+
+        let need = strategy.min_bars();
+        if self.next_bar < need {
+            return Err(BacktestError::InsufficientData {
+                have: self.next_bar,
+                need,
+            }
+            .into());
+        }
+
+        let results = self.into_results()?;
+        strategy.on_finish(&results)?;
+        Ok(results)
+    }
+
+    /// Process the next bar, returning `false` once every bar has been processed
+    pub fn step<S: Strategy>(&mut self, strategy: &mut S) -> Result<bool> {
+        let data = self.data;
+        let Some(bar) = data.get(self.next_bar) else {
+            return Ok(false);
+        };
+        let next_timestamp = data.get(self.next_bar + 1).map(|next| next.timestamp);
+
+        self.process_bar(strategy, bar, next_timestamp)?;
+        Ok(true)
+    }
+
+    /// Process `bar` as the next bar. `next_timestamp` is that of the bar after it,
+    /// `None` if it is the last one.
+    fn process_bar<S: Strategy>(
+        &mut self,
+        strategy: &mut S,
+        bar: &OHLCV,
+        next_timestamp: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let index = self.next_bar;
+        self.current_bar_index = index;
+        let held_at_open = !self.positions.is_empty();
+
+        // Charge financing and credit idle-cash interest since the previous bar
+        if let Some(&(previous, _)) = self.equity_curve.last() {
+            self.accrue_financing(previous, bar);
+            self.accrue_cash_interest(previous, bar);
+        }
+
+        // Track the buy-and-hold benchmark as the bars go by, entering where the first
+        // market order could fill
+        if self.buy_hold_entry.is_none() && (index > 0 || !self.config.defers_market_orders()) {
+            self.buy_hold_entry = Some(match self.config.fills_on_open() {
+                true => bar.open,
+                false => bar.close,
+            });
+        }
+        self.last_close = bar.close;
 
-        // Run backtest
-        for (index, bar) in self.data.iter().enumerate() {
-            self.current_bar_index = index;
+        // Exit at stop-loss or take-profit levels reached during the bar
+        self.check_exit_levels(bar)?;
 
-            // Get orders from strategy
-            let orders = strategy.next(bar, index)?;
+        // Fill resting limit and stop orders the bar reached
+        self.process_pending_orders(bar)?;
+        self.report_closed_trades(strategy)?;
 
-            // Process orders
-            for order in orders {
-                self.process_order(order, bar)?;
+        // Get orders from strategy
+        self.context.advance(bar.timestamp);
+        // The positions are lent to the context for the strategy call, not copied
+        let equity = self.calculate_equity();
+        let positions = std::mem::take(&mut self.positions);
+        self.context.sync_broker(positions, self.cash, equity);
+        self.context.sync_orders(&self.pending_orders);
+        let signal = match self.config.fill_timing {
+            FillTiming::Standard | FillTiming::NextBarOpen => {
+                strategy.next_with_context(&mut self.context, bar, index)
             }
+            FillTiming::SignalAndFillOnOpen => {
+                let open_only = Self::open_only(bar);
+                strategy.next_with_context(&mut self.context, &open_only, index)
+            }
+        };
+        self.positions = self.context.take_positions();
+        let mut orders = signal?;
+        orders.extend(self.context.take_submitted_orders());
 
-            // Update position current price if we have one
-            if let Some(ref mut position) = self.current_position {
-                position.update_price(bar.close);
+        // Drop the resting orders the strategy cancelled through the context
+        for (order, seen) in self
+            .pending_orders
+            .iter_mut()
+            .zip(self.context.open_orders())
+        {
+            if seen.is_cancelled() {
+                order.cancel();
             }
+        }
+        self.pending_orders.retain(|order| !order.is_cancelled());
 
-            // Update equity curve
-            let equity = self.calculate_equity(bar);
-            self.equity_curve.push((bar.timestamp, equity));
+        let orders = self.confirm_orders(orders);
+        self.collect_indicators(bar);
+
+        // Process orders
+        for order in orders {
+            self.process_order(order, bar)?;
         }
 
-        // Calculate final results
-        self.calculate_results()
+        // Update position current prices and ratchet any trailing stops for the next bar.
+        // A position opened on this bar keeps the stop seeded from its fill price, as the
+        // bar's extremes may have printed before it filled.
+        for position in &mut self.positions {
+            position.update_price(bar.close);
+            if position.entry_bar != index {
+                position.trail_stop(bar.high, bar.low);
+            }
+        }
+
+        // Liquidate positions whose equity no longer covers the maintenance margin
+        self.check_margin_call(bar)?;
+        self.report_closed_trades(strategy)?;
+
+        // Charge the performance fee on the last bar of each fee period
+        if let Some(fee) = self.config.performance_fee {
+            let period_end = match next_timestamp {
+                Some(next) => fee.period.is_new_period(bar.timestamp, next),
+                None => true,
+            };
+            if period_end {
+                self.assess_performance_fee(fee, bar);
+            }
+        }
+
+        // A bar counts as exposed if a position was carried into it or is held after it
+        if held_at_open || !self.positions.is_empty() {
+            self.exposed_bars += 1;
+        }
+
+        // Update equity curve
+        let equity = self.calculate_equity();
+        self.equity_curve.push((bar.timestamp, equity));
+
+        self.next_bar += 1;
+        Ok(())
+    }
+
+    /// Compute results over the bars processed so far.
+    ///
+    /// The engine state is only read, so stepping and checkpointing can carry on
+    /// afterwards; the results hold copies of the trades and series.
+    pub fn finalize(&self) -> Result<BacktestResults> {
+        let mut results = self.calculate_results()?;
+        results.trades = self.trades.clone();
+        results.ledger = self.ledger.clone();
+        results.indicator_series = self.indicator_series.clone();
+        results.equity_curve = self.equity_curve.clone();
+        Ok(results)
+    }
+
+    /// Compute the final results, moving the trades and series into them
+    fn into_results(mut self) -> Result<BacktestResults> {
+        let mut results = self.calculate_results()?;
+        results.trades = std::mem::take(&mut self.trades);
+        results.ledger = std::mem::take(&mut self.ledger);
+        results.indicator_series = std::mem::take(&mut self.indicator_series);
+        results.equity_curve = std::mem::take(&mut self.equity_curve);
+        Ok(results)
+    }
+
+    /// Snapshot the engine state after the bars processed so far
+    pub fn checkpoint(&self) -> BacktestCheckpoint {
+        // Destructure so a new engine field can't be silently left out of the snapshot
+        let Backtest {
+            config: _,
+            data: _,
+            positions,
+            cash,
+            equity_curve,
+            trades,
+            trade_summary,
+            current_bar_index,
+            next_bar,
+            high_water_mark,
+            exposed_bars,
+            buy_hold_entry,
+            last_close,
+            context,
+            awaiting_confirmation,
+            pending_orders,
+            ledger,
+            indicator_series,
+            unreported_trades: _,
+            next_position_id,
+        } = self;
+
+        BacktestCheckpoint {
+            positions: positions.clone(),
+            cash: *cash,
+            equity_curve: equity_curve.clone(),
+            trades: trades.clone(),
+            trade_summary: trade_summary.clone(),
+            current_bar_index: *current_bar_index,
+            next_bar: *next_bar,
+            high_water_mark: *high_water_mark,
+            exposed_bars: *exposed_bars,
+            buy_hold_entry: *buy_hold_entry,
+            last_close: *last_close,
+            context: context.clone(),
+            awaiting_confirmation: awaiting_confirmation.clone(),
+            pending_orders: pending_orders.clone(),
+            ledger: ledger.clone(),
+            indicator_series: indicator_series.clone(),
+            next_position_id: *next_position_id,
+        }
+    }
+
+    /// Replace the engine state with a snapshot taken by `checkpoint`.
+    ///
+    /// The engine's data must contain the bars the checkpoint was taken over; stepping
+    /// continues from the first bar after them.
+    pub fn restore(&mut self, checkpoint: BacktestCheckpoint) -> Result<()> {
+        if checkpoint.next_bar > self.data.len() {
+            return Err(BacktestError::InsufficientData {
+                have: self.data.len(),
+                need: checkpoint.next_bar,
+            }
+            .into());
+        }
+
+        let BacktestCheckpoint {
+            positions,
+            cash,
+            equity_curve,
+            trades,
+            trade_summary,
+            current_bar_index,
+            next_bar,
+            high_water_mark,
+            exposed_bars,
+            buy_hold_entry,
+            last_close,
+            context,
+            awaiting_confirmation,
+            pending_orders,
+            ledger,
+            indicator_series,
+            next_position_id,
+        } = checkpoint;
+
+        self.positions = positions;
+        self.cash = cash;
+        self.equity_curve = equity_curve;
+        self.trades = trades;
+        self.trade_summary = trade_summary;
+        self.current_bar_index = current_bar_index;
+        self.next_bar = next_bar;
+        self.high_water_mark = high_water_mark;
+        self.exposed_bars = exposed_bars;
+        self.buy_hold_entry = buy_hold_entry;
+        self.last_close = last_close;
+        self.context = context;
+        self.awaiting_confirmation = awaiting_confirmation;
+        self.pending_orders = pending_orders;
+        self.ledger = ledger;
+        self.indicator_series = indicator_series;
+        self.next_position_id = next_position_id;
+        Ok(())
+    }
+
+    /// Pass the trades closed since the last call to `Strategy::on_trade_close`
+    fn report_closed_trades<S: Strategy>(&mut self, strategy: &mut S) -> Result<()> {
+        for trade in std::mem::take(&mut self.unreported_trades) {
+            strategy.on_trade_close(&trade)?;
+        }
+        Ok(())
+    }
+
+    /// Append the values recorded through the context this bar to their series,
+    /// padding with `None` so every series stays aligned to the bars
+    fn collect_indicators(&mut self, bar: &OHLCV) {
+        let index = self.current_bar_index;
+        let equity_curve = &self.equity_curve;
+
+        for (name, value) in self.context.take_recorded_indicators() {
+            // The equity curve holds a point for every earlier bar
+            let series = self.indicator_series.entry(name).or_insert_with(|| {
+                equity_curve
+                    .iter()
+                    .map(|&(timestamp, _)| (timestamp, None))
+                    .collect()
+            });
+
+            if series.len() > index {
+                series[index].1 = Some(value);
+            } else {
+                series.push((bar.timestamp, Some(value)));
+            }
+        }
+
+        for series in self.indicator_series.values_mut() {
+            if series.len() <= index {
+                series.push((bar.timestamp, None));
+            }
+        }
+    }
+
+    /// Hold back orders with `confirm_bars > 1` until the same-side order has been
+    /// submitted on that many consecutive bars. A streak that is not continued on
+    /// the next bar is reset and its order discarded.
+    fn confirm_orders(&mut self, orders: Vec<Order>) -> Vec<Order> {
+        let previous = std::mem::take(&mut self.awaiting_confirmation);
+        let mut ready = Vec::with_capacity(orders.len());
+
+        for order in orders {
+            if order.confirm_bars <= 1 {
+                ready.push(order);
+                continue;
+            }
+
+            let streak = previous
+                .iter()
+                .find(|(pending, _)| pending.side == order.side)
+                .map_or(0, |(_, count)| *count)
+                + 1;
+
+            if streak >= order.confirm_bars {
+                ready.push(order);
+            } else {
+                self.awaiting_confirmation.push((order, streak));
+            }
+        }
+
+        ready
+    }
+
+    /// Close positions at their stop loss or take profit if the bar reached either,
+    /// starting with the bar after each one's entry
+    fn check_exit_levels(&mut self, bar: &OHLCV) -> Result<()> {
+        for index in (0..self.positions.len()).rev() {
+            let position = &self.positions[index];
+            if position.entry_bar >= self.current_bar_index {
+                continue;
+            }
+
+            let (above, below) = if position.is_long() {
+                (position.tp, position.sl)
+            } else {
+                (position.sl, position.tp)
+            };
+            let size = position.size.abs();
+            let is_long = position.is_long();
+
+            if let Some(fill_price) = self.config.intrabar_path.first_touched(
+                bar,
+                self.current_bar_index,
+                is_long,
+                above,
+                below,
+            ) {
+                self.close_position(index, size, fill_price, bar)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The part of `bar` known at the opening print
+    fn open_only(bar: &OHLCV) -> OHLCV {
+        OHLCV {
+            timestamp: bar.timestamp,
+            open: bar.open,
+            high: f64::NAN,
+            low: f64::NAN,
+            close: f64::NAN,
+            volume: f64::NAN,
+        }
     }
 
     fn process_order(&mut self, order: Order, bar: &OHLCV) -> Result<()> {
-        let price = match self.config.trade_on_open {
+        if self.config.exclusive_orders && !order.flatten {
+            self.pending_orders.clear();
+        }
+
+        // Limit and stop orders rest until a later bar reaches their price, and market
+        // orders wait for the next open when filling on this bar would peek ahead
+        let is_market = order.order_type == OrderType::Market || order.flatten;
+        if !is_market || self.config.defers_market_orders() {
+            self.pending_orders.push(order);
+            return Ok(());
+        }
+
+        let price = match self.config.fills_on_open() {
             true => bar.open,
             false => bar.close,
         };
 
-        match order.side {
-            OrderSide::Buy => self.open_position(order.size, price, bar.timestamp, bar.close)?,
-            OrderSide::Sell => self.close_position(order.size, price, bar)?,
+        let mut order = order;
+        self.fill_order(&mut order, price, bar, true)?;
+        self.rest_remainder(order);
+        Ok(())
+    }
+
+    /// Fill resting limit and stop orders whose trigger price the bar reached, and
+    /// market orders deferred to this bar's open. Orders that don't fill carry over to
+    /// the next bar.
+    fn process_pending_orders(&mut self, bar: &OHLCV) -> Result<()> {
+        for mut order in std::mem::take(&mut self.pending_orders) {
+            if order.is_cancelled() {
+                continue;
+            }
+
+            match Self::resting_fill_price(&mut order, bar) {
+                // Stops become market orders once triggered, so they take slippage
+                Some((price, slipped)) => {
+                    self.fill_order(&mut order, price, bar, slipped)?;
+                    self.rest_remainder(order);
+                }
+                None => self.pending_orders.push(order),
+            }
         }
 
         Ok(())
     }
 
-    fn open_position(
+    /// Price a resting order fills at on `bar` and whether it takes slippage, as market
+    /// and triggered stop orders do.
+    ///
+    /// Limits fill at the limit, or at the open if the bar gapped through it; stops
+    /// fill at the stop, or at the open on a gap. A triggered stop-limit turns into a
+    /// limit order that can fill on the same bar.
+    fn resting_fill_price(order: &mut Order, bar: &OHLCV) -> Option<(f64, bool)> {
+        let limit_fill = |side: OrderSide, limit: f64| match side {
+            OrderSide::Buy => (bar.low <= limit).then(|| limit.min(bar.open)),
+            OrderSide::Sell => (bar.high >= limit).then(|| limit.max(bar.open)),
+        };
+        let stop_fill = |side: OrderSide, stop: f64| match side {
+            OrderSide::Buy => (bar.high >= stop).then(|| stop.max(bar.open)),
+            OrderSide::Sell => (bar.low <= stop).then(|| stop.min(bar.open)),
+        };
+
+        match order.order_type {
+            OrderType::Market => Some((bar.open, true)),
+            OrderType::Limit => limit_fill(order.side, order.limit?).map(|price| (price, false)),
+            OrderType::Stop => stop_fill(order.side, order.stop?).map(|price| (price, true)),
+            OrderType::StopLimit => {
+                stop_fill(order.side, order.stop?)?;
+                order.order_type = OrderType::Limit;
+                limit_fill(order.side, order.limit?).map(|price| (price, false))
+            }
+        }
+    }
+
+    /// Execute `order` at `price`, moved by the slippage model when `apply_slippage` is
+    /// set.
+    ///
+    /// An order cut short by the buying power is left `PartiallyFilled`, with its
+    /// `size` converted to units so `remaining_size()` is what's left to fill.
+    fn fill_order(
         &mut self,
-        size: f64,
+        order: &mut Order,
         price: f64,
-        timestamp: DateTime<Utc>,
-        current_price: f64,
+        bar: &OHLCV,
+        apply_slippage: bool,
     ) -> Result<()> {
-        let cost = size * price * (1.0 + self.config.commission);
-
-        if cost > self.cash {
-            return Ok(()); // Insufficient funds
+        if order.flatten {
+            let fraction = order.size.clamp(0.0, 1.0);
+            return self.close_fraction(order.position_id, fraction, price, bar, apply_slippage);
         }
 
-        self.cash -= cost;
+        // The remainder of a partial fill must not close the position its first fill
+        // opened
+        let is_new = order.status == OrderStatus::Pending;
+        if self.config.exclusive_orders && is_new {
+            self.close_fraction(None, 1.0, price, bar, apply_slippage)?;
+        }
 
-        if let Some(ref mut position) = self.current_position {
-            // Update existing position
-            let total_cost = position.size * position.entry_price + size * price;
-            let total_size = position.size + size;
-            position.entry_price = total_cost / total_size;
-            position.size = total_size;
-            position.update_price(current_price);
-        } else {
-            // Create new position
-            let mut new_position = Position::new(size, price, timestamp);
-            new_position.update_price(current_price);
-            self.current_position = Some(new_position);
-            self.position_entry_bar = Some(self.current_bar_index);
+        let size = match order.size_unit {
+            SizeUnit::EquityFraction => accounting::equity_fraction_units(
+                &self.config,
+                order,
+                self.calculate_equity(),
+                price,
+                bar,
+                apply_slippage,
+            ),
+            SizeUnit::Units => order.remaining_size(),
+        };
+        let size = self.config.tradable_units(size);
+        if size <= 0.0 {
+            return Ok(());
         }
 
+        let symbol = order.symbol.as_deref();
+        let fill_price = accounting::slipped_price(
+            &self.config,
+            symbol,
+            order.side,
+            price,
+            size,
+            bar,
+            apply_slippage,
+        );
+        let unfilled = self.apply_fill(order, order.side, size, fill_price, bar)?;
+        let fill = Fill {
+            size,
+            price: fill_price,
+            bar_index: self.current_bar_index,
+            timestamp: bar.timestamp,
+        };
+        accounting::settle_order(order, &fill, unfilled);
         Ok(())
     }
 
-    fn close_position(&mut self, size: f64, price: f64, current_bar: &OHLCV) -> Result<()> {
-        if let Some(ref mut position) = self.current_position {
-            let close_size = size.min(position.size);
-            let proceeds = close_size * price * (1.0 - self.config.commission);
-            let _cost_basis = close_size * position.entry_price;
-
-            self.cash += proceeds;
+    /// Keep the unfilled part of a partially filled order resting when
+    /// `PartialFills::RestRemainder` is configured. A triggered stop rests as a market
+    /// order.
+    fn rest_remainder(&mut self, mut order: Order) {
+        let rests = self.config.partial_fills == PartialFills::RestRemainder;
+        if rests && order.status == OrderStatus::PartiallyFilled {
+            if order.order_type == OrderType::Stop {
+                order.order_type = OrderType::Market;
+            }
+            self.pending_orders.push(order);
+        }
+    }
 
-            // Create trade record
-            let mut trade = Trade::new(
-                self.position_entry_bar.unwrap_or(0),
-                position.entry_price,
-                position.entry_time,
-                close_size,
-                None, // sl
-                None, // tp
-                None, // tag
+    /// Close the same fraction of every open position, or only of the one with
+    /// `position_id` when given, at `price` moved by slippage against each exit
+    fn close_fraction(
+        &mut self,
+        position_id: Option<usize>,
+        fraction: f64,
+        price: f64,
+        bar: &OHLCV,
+        apply_slippage: bool,
+    ) -> Result<()> {
+        for index in (0..self.positions.len()).rev() {
+            let position = &self.positions[index];
+            if position_id.is_some_and(|id| id != position.id) {
+                continue;
+            }
+            let size = self.config.tradable_units(position.size.abs() * fraction);
+            if size <= 0.0 {
+                continue;
+            }
+            let side = if position.is_long() {
+                OrderSide::Sell
+            } else {
+                OrderSide::Buy
+            };
+            let symbol = position.symbol.as_deref();
+            let price = accounting::slipped_price(
+                &self.config,
+                symbol,
+                side,
+                price,
+                size,
+                bar,
+                apply_slippage,
             );
-            trade.close(Some(self.current_bar_index), price, current_bar.timestamp);
+            self.close_position(index, size, price, bar)?;
+        }
 
-            self.trades.push(trade);
+        Ok(())
+    }
 
-            // Update position
-            position.size -= close_size;
-            if position.size <= 0.0 {
-                self.current_position = None;
-                self.position_entry_bar = None;
+    /// Apply a fill of `size` units on `side`.
+    ///
+    /// When netting, an opposite position is reduced or closed first and any remaining
+    /// quantity opens or adds to a position on `side`, so a sell larger than the open
+    /// long closes it and goes short the difference. With `hedging` every fill opens a
+    /// separate position. Returns the units left unfilled for lack of buying power.
+    fn apply_fill(
+        &mut self,
+        order: &Order,
+        side: OrderSide,
+        size: f64,
+        price: f64,
+        bar: &OHLCV,
+    ) -> Result<f64> {
+        let mut remaining = size;
+        if !self.config.hedging {
+            if let Some(position) = self.positions.first() {
+                if position.is_long() != (side == OrderSide::Buy) {
+                    let close_size = remaining.min(position.size.abs());
+                    self.close_position(0, close_size, price, bar)?;
+                    remaining -= close_size;
+                }
             }
+        }
+
+        if remaining > 0.0 {
+            return self.open_position(order, side, remaining, price, bar);
+        }
+
+        Ok(0.0)
+    }
+
+    /// Open or add to a position, returning the units left unfilled.
+    ///
+    /// Every opening fill, scale-ins included, must leave the open positions within the
+    /// buying power of `equity / margin` after paying its commission. A fill that
+    /// doesn't is rejected whole, or cut down to the affordable units when
+    /// `partial_fills` allows it.
+    fn open_position(
+        &mut self,
+        order: &Order,
+        side: OrderSide,
+        size: f64,
+        price: f64,
+        bar: &OHLCV,
+    ) -> Result<f64> {
+        let requested = size;
+        let size = BuyingPower::of(self.cash, self.positions.iter()).fillable_units(
+            &self.config,
+            order.symbol.as_deref(),
+            size,
+            price,
+        );
+        if size <= 0.0 {
+            return Ok(requested); // Insufficient buying power
+        }
+
+        let fill = Fill {
+            size,
+            price,
+            bar_index: self.current_bar_index,
+            timestamp: bar.timestamp,
+        };
+        if self.config.hedging || self.positions.is_empty() {
+            let mut position = Position::new(0.0, price, bar.timestamp);
+            position.id = self.next_position_id;
+            self.next_position_id += 1;
+            self.positions.push(position);
+        }
+        let index = if self.config.hedging {
+            self.positions.len() - 1
         } else {
-            // No position to close - this is expected behavior, just skip
+            0
+        };
+        let position = &mut self.positions[index];
+        let commission = accounting::open(position, &self.config, order, side, &fill);
+        position.update_price(bar.close);
+
+        // Short sale proceeds are not credited; the short's P&L settles on cover
+        if side == OrderSide::Buy {
+            self.post_cash(bar.timestamp, LedgerEventType::Buy, -size * price);
+        }
+        if commission > 0.0 {
+            self.post_cash(bar.timestamp, LedgerEventType::Commission, -commission);
+        }
+
+        Ok(requested - size)
+    }
+
+    /// Close `size` units of the position at `index`, removing it once fully closed.
+    ///
+    /// Units come off the position's lots in `lot_matching` order and each closed lot
+    /// is recorded as its own trade with that lot's entry.
+    fn close_position(
+        &mut self,
+        index: usize,
+        size: f64,
+        price: f64,
+        current_bar: &OHLCV,
+    ) -> Result<()> {
+        let Some(position) = self.positions.get_mut(index) else {
+            return Ok(()); // No position to close
+        };
+        let fill = Fill {
+            size,
+            price,
+            bar_index: self.current_bar_index,
+            timestamp: current_bar.timestamp,
+        };
+        let exit = accounting::close(position, &self.config, &fill);
+        if position.size == 0.0 {
+            self.positions.remove(index);
+        }
+
+        for trade in exit.trades {
+            // Earlier bars are on the equity curve, this one isn't yet
+            let entry_bar = trade.entry_bar.min(self.equity_curve.len());
+            let held_bars = self.equity_curve[entry_bar..]
+                .iter()
+                .map(|&(timestamp, _)| timestamp)
+                .chain(std::iter::once(current_bar.timestamp));
+            let duration_units =
+                Calculations::trade_duration(&trade, self.config.duration_unit, held_bars);
+            self.trade_summary.record(&trade, duration_units);
+            self.unreported_trades.push(trade.clone());
+            match self.config.trade_sink {
+                Some(ref sink) => sink.write_trade(&trade)?,
+                None => self.trades.push(trade),
+            }
+        }
+
+        let event = match exit.was_long {
+            true => LedgerEventType::Sell,
+            false => LedgerEventType::Buy,
+        };
+        self.post_cash(current_bar.timestamp, event, exit.settlement);
+        if exit.commission > 0.0 {
+            self.post_cash(
+                current_bar.timestamp,
+                LedgerEventType::Commission,
+                -exit.commission,
+            );
         }
 
         Ok(())
     }
 
-    fn calculate_equity(&self, _bar: &OHLCV) -> f64 {
-        let mut equity = self.cash;
+    fn check_margin_call(&mut self, bar: &OHLCV) -> Result<()> {
+        let gross_value: f64 = self.positions.iter().map(|p| p.value().abs()).sum();
+        if self.positions.is_empty()
+            || self.calculate_equity() >= gross_value * self.config.maintenance_margin
+        {
+            return Ok(());
+        }
+
+        for index in (0..self.positions.len()).rev() {
+            let size = self.positions[index].size.abs();
+            self.close_position(index, size, bar.close, bar)?;
+        }
+
+        Ok(())
+    }
+
+    fn assess_performance_fee(&mut self, fee: PerformanceFee, bar: &OHLCV) {
+        let equity = self.calculate_equity();
+
+        if equity > self.high_water_mark {
+            let charge = (equity - self.high_water_mark) * fee.rate;
+            self.post_cash(bar.timestamp, LedgerEventType::PerformanceFee, -charge);
+            self.high_water_mark = equity - charge;
+        }
+    }
+
+    /// Debit financing on the borrowed part of leveraged longs and on the full
+    /// notional of shorts, pro-rated by the time elapsed since `previous`
+    fn accrue_financing(&mut self, previous: DateTime<Utc>, bar: &OHLCV) {
+        if self.config.financing_rate <= 0.0 {
+            return;
+        }
+
+        let short_notional: f64 = self
+            .positions
+            .iter()
+            .filter(|p| p.is_short())
+            .map(|p| p.value().abs())
+            .sum();
+        let long_value: f64 = self
+            .positions
+            .iter()
+            .filter(|p| p.is_long())
+            .map(|p| p.value())
+            .sum();
+        let borrowed = short_notional + (long_value - self.calculate_equity()).max(0.0);
+
+        let charge = borrowed
+            * self.config.financing_rate
+            * Calculations::year_fraction(previous, bar.timestamp);
+        if charge > 0.0 {
+            self.post_cash(bar.timestamp, LedgerEventType::Financing, -charge);
+        }
+    }
+
+    /// Credit interest on the cash balance held since `previous`
+    fn accrue_cash_interest(&mut self, previous: DateTime<Utc>, bar: &OHLCV) {
+        if self.config.cash_interest_rate <= 0.0 || self.cash <= 0.0 {
+            return;
+        }
 
-        if let Some(ref position) = self.current_position {
-            equity += position.value();
+        let interest = self.cash
+            * self.config.cash_interest_rate
+            * Calculations::year_fraction(previous, bar.timestamp);
+        if interest > 0.0 {
+            self.post_cash(bar.timestamp, LedgerEventType::Interest, interest);
         }
+    }
+
+    /// Apply a cash movement and record it in the ledger
+    fn post_cash(&mut self, timestamp: DateTime<Utc>, event: LedgerEventType, amount: f64) {
+        self.cash += amount;
+        self.ledger.push(LedgerEntry {
+            timestamp,
+            event,
+            amount,
+            cash: self.cash,
+            equity: self.calculate_equity(),
+        });
+    }
 
-        equity
+    fn calculate_equity(&self) -> f64 {
+        accounting::equity(self.cash, self.positions.iter())
     }
 
-    fn calculate_results(&mut self) -> Result<BacktestResults> {
-        if self.data.is_empty() || self.equity_curve.is_empty() {
+    /// Return of buying at the first price a market order could fill at and holding to
+    /// the last close, net of commission when the config asks for it
+    fn buy_hold_return(&self) -> f64 {
+        let exit_price = self.last_close;
+        let entry_price = match self.buy_hold_entry {
+            Some(price) if price > 0.0 => price,
+            _ => return 0.0,
+        };
+
+        let initial_cash = self.config.initial_cash;
+        let units = initial_cash / entry_price;
+        let commission = if self.config.buy_hold_net_of_commission {
+            self.config.commission_for(None, units, entry_price)
+                + self.config.commission_for(None, units, exit_price)
+        } else {
+            0.0
+        };
+
+        (units * exit_price - commission - initial_cash) / initial_cash
+    }
+
+    /// Statistics over the bars processed so far, with the trades and series left empty
+    /// for the caller to fill in
+    fn calculate_results(&self) -> Result<BacktestResults> {
+        if self.equity_curve.is_empty() {
             return Err("No data or equity curve available".into());
         }
 
-        let start_date = self.data.first().unwrap().timestamp;
-        let end_date = self.data.last().unwrap().timestamp;
+        let start_date = self.equity_curve.first().unwrap().0;
+        let end_date = self.equity_curve.last().unwrap().0;
         let duration = end_date - start_date;
 
         let initial_equity = self.config.initial_cash;
         let final_equity = self.equity_curve.last().unwrap().1;
 
         let return_pct = (final_equity - initial_equity) / initial_equity;
-        let buy_hold_return = (self.data.last().unwrap().close - self.data.first().unwrap().close)
-            / self.data.first().unwrap().close;
+        let buy_hold_return = self.buy_hold_return();
 
         // Calculate basic trade statistics
-        let win_rate = if self.trades.is_empty() {
-            0.0
-        } else {
-            self.trades.iter().filter(|t| t.pl() > 0.0).count() as f64 / self.trades.len() as f64
-        };
+        let summary = &self.trade_summary;
+        let win_rate = summary.win_rate();
+        let has_trades = summary.count > 0;
+        let best_trade = has_trades.then_some(summary.best);
+        let worst_trade = has_trades.then_some(summary.worst);
+        let avg_trade = has_trades.then(|| summary.avg_pl());
+        let mut trade_stats = summary.trade_stats();
+        trade_stats.returns = self.trades.iter().map(Trade::pl_pct).collect();
 
-        let best_trade = self.trades.iter().map(|t| t.pl()).fold(0.0, f64::max);
-        let worst_trade = self.trades.iter().map(|t| t.pl()).fold(0.0, f64::min);
-        let avg_trade = if self.trades.is_empty() {
-            0.0
-        } else {
-            self.trades.iter().map(|t| t.pl()).sum::<f64>() / self.trades.len() as f64
-        };
+        let total_commissions = self
+            .ledger
+            .iter()
+            .filter(|entry| entry.event == LedgerEventType::Commission)
+            .fold(0.0, |total, entry| total - entry.amount);
+
+        // Durations are summarized as trades close, so they hold with a trade sink too
+        let max_trade_duration = summary.max_duration;
+        let avg_trade_duration = summary.avg_duration();
 
         // Calculate exposure time
-        let exposure_time = Calculations::calculate_exposure_time(self.data, &self.trades);
+        let exposure_time =
+            Calculations::calculate_exposure_time(self.exposed_bars, self.equity_curve.len());
 
         // Calculate annualized metrics
-        let years = duration.num_days() as f64 / 365.25;
+        let years = Calculations::year_fraction(start_date, end_date);
         let return_ann = if years > 0.0 {
             (1.0 + return_pct).powf(1.0 / years) - 1.0
         } else {
@@ -262,22 +1514,37 @@ impl<'a> Backtest<'a> {
         };
 
         // Calculate volatility (annualized standard deviation of returns)
-        let volatility_ann = Calculations::calculate_volatility(&self.equity_curve, years);
+        let return_basis = self.config.return_basis;
+        let periods_per_year = self.config.periods_per_year.unwrap_or_else(|| {
+            let timestamps: Vec<DateTime<Utc>> = self
+                .equity_curve
+                .iter()
+                .map(|&(timestamp, _)| timestamp)
+                .collect();
+            Calculations::infer_periods_per_year_from(&timestamps)
+        });
+        let volatility_ann =
+            Calculations::calculate_volatility(&self.equity_curve, periods_per_year, return_basis);
 
         // Calculate drawdown metrics
         let (max_drawdown, avg_drawdown, max_dd_duration, avg_dd_duration) =
             Calculations::calculate_drawdown_metrics(&self.equity_curve);
 
         // Calculate risk-adjusted ratios
-        let risk_free_rate = 0.02; // Assume 2% risk-free rate
+        let risk_free_rate = self.config.risk_free_rate;
         let sharpe_ratio = if volatility_ann > 0.0 {
-            (return_ann - risk_free_rate) / volatility_ann
+            return_basis.excess_return(return_ann, risk_free_rate) / volatility_ann
         } else {
             0.0
         };
 
-        let sortino_ratio =
-            Calculations::calculate_sortino_ratio(&self.equity_curve, return_ann, risk_free_rate);
+        let sortino_ratio = Calculations::calculate_sortino_ratio(
+            &self.equity_curve,
+            return_ann,
+            risk_free_rate,
+            periods_per_year,
+            return_basis,
+        );
 
         let calmar_ratio = if max_drawdown.abs() > 0.0 {
             return_ann / max_drawdown.abs()
@@ -286,30 +1553,29 @@ impl<'a> Backtest<'a> {
         };
 
         // Calculate profit factor
-        let gross_profit: f64 = self.trades.iter()
-            .filter(|t| t.pl() > 0.0)
-            .map(|t| t.pl())
-            .sum();
-        let gross_loss: f64 = self.trades.iter()
-            .filter(|t| t.pl() < 0.0)
-            .map(|t| t.pl().abs())
-            .sum();
-        let profit_factor = if gross_loss > 0.0 {
-            gross_profit / gross_loss
-        } else if gross_profit > 0.0 {
-            f64::INFINITY
-        } else {
-            0.0
-        };
+        let profit_factor = summary.profit_factor();
 
         // Calculate System Quality Number (SQN)
-        let sqn = Calculations::calculate_sqn(&self.trades);
+        let sqn = summary.sqn();
+
+        // Calculate trade durations in the configured unit
+        let max_trade_duration_units = summary.max_duration_units;
+        let avg_trade_duration_units = summary.avg_duration_units();
+
+        // Calculate R-multiple statistics for trades with a stop loss
+        let (avg_r, expectancy_r) = summary.r_expectancy();
+        let r_multiples = self.trades.iter().filter_map(Trade::r_multiple).collect();
+
+        if let Some(ref sink) = self.config.trade_sink {
+            sink.flush()?;
+        }
 
         Ok(BacktestResults {
             start_date,
             end_date,
             duration,
             exposure_time,
+            initial_equity,
             equity_final: final_equity,
             equity_peak: self
                 .equity_curve
@@ -327,27 +1593,31 @@ impl<'a> Backtest<'a> {
             avg_drawdown,
             max_drawdown_duration: max_dd_duration,
             avg_drawdown_duration: avg_dd_duration,
-            trades: std::mem::take(&mut self.trades),
+            value_at_risk_95: Calculations::value_at_risk(
+                &Calculations::daily_returns(&self.equity_curve),
+                0.95,
+            ),
+            trades: Vec::new(),
+            trade_count: summary.count,
             win_rate,
             best_trade,
             worst_trade,
             avg_trade,
-            max_trade_duration: self
-                .trades
-                .iter()
-                .map(|t| t.duration())
-                .max()
-                .unwrap_or(chrono::Duration::zero()),
-            avg_trade_duration: if self.trades.is_empty() {
-                chrono::Duration::zero()
-            } else {
-                let total_duration: chrono::Duration =
-                    self.trades.iter().map(|t| t.duration()).sum();
-                total_duration / self.trades.len() as i32
-            },
+            total_commissions,
+            trade_stats,
+            max_trade_duration,
+            avg_trade_duration,
+            max_trade_duration_units,
+            avg_trade_duration_units,
             profit_factor,
             expectancy: avg_trade,
             sqn,
+            r_multiples,
+            avg_r,
+            expectancy_r,
+            ledger: Vec::new(),
+            indicator_series: HashMap::new(),
+            equity_curve: Vec::new(),
         })
     }
 }
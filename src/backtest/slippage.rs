@@ -0,0 +1,90 @@
+use crate::order::OrderSide;
+use crate::types::OHLCV;
+use std::fmt::Debug;
+
+/// How fill prices deviate from the quoted bar price
+pub trait SlippageModel: Debug + Send + Sync {
+    /// Price slippage per unit for an order of `size` on `bar`
+    fn slippage(&self, price: f64, size: f64, bar: &OHLCV) -> f64;
+
+    /// Quoted price moved against the order: up for buys, down for sells
+    fn adjust_fill(&self, side: OrderSide, price: f64, size: f64, bar: &OHLCV) -> f64 {
+        let slippage = self.slippage(price, size, bar);
+        match side {
+            OrderSide::Buy => price + slippage,
+            OrderSide::Sell => price - slippage,
+        }
+    }
+}
+
+/// Fill exactly at the quoted price
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NoSlippage;
+
+impl SlippageModel for NoSlippage {
+    fn slippage(&self, _price: f64, _size: f64, _bar: &OHLCV) -> f64 {
+        0.0
+    }
+}
+
+/// Fixed fraction of the price in basis points, e.g. `5.0` for 0.05%
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedBps {
+    pub bps: f64,
+}
+
+impl SlippageModel for FixedBps {
+    fn slippage(&self, price: f64, _size: f64, _bar: &OHLCV) -> f64 {
+        price * self.bps / 10_000.0
+    }
+}
+
+/// Fixed number of ticks of `tick_size` away from the quoted price
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedTicks {
+    pub ticks: f64,
+    pub tick_size: f64,
+}
+
+impl SlippageModel for FixedTicks {
+    fn slippage(&self, _price: f64, _size: f64, _bar: &OHLCV) -> f64 {
+        self.ticks * self.tick_size
+    }
+}
+
+/// Linear market impact: `rate * (size / bar.volume) * price`.
+///
+/// Bars without volume incur no impact.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeProportional {
+    pub rate: f64,
+}
+
+impl SlippageModel for VolumeProportional {
+    fn slippage(&self, price: f64, size: f64, bar: &OHLCV) -> f64 {
+        if bar.volume <= 0.0 || size <= 0.0 {
+            0.0
+        } else {
+            self.rate * (size / bar.volume) * price
+        }
+    }
+}
+
+/// Square-root market impact: `k * sqrt(size / bar.volume) * price`.
+///
+/// Larger orders relative to the bar's traded volume move the price further.
+/// Bars without volume incur no impact.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SquareRootImpact {
+    pub k: f64,
+}
+
+impl SlippageModel for SquareRootImpact {
+    fn slippage(&self, price: f64, size: f64, bar: &OHLCV) -> f64 {
+        if bar.volume <= 0.0 || size <= 0.0 {
+            0.0
+        } else {
+            self.k * (size / bar.volume).sqrt() * price
+        }
+    }
+}
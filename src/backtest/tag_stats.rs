@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// Key `BacktestResults::performance_by_tag` files trades without a tag under
+pub const UNTAGGED: &str = "untagged";
+
+/// Performance of the closed trades sharing one entry tag
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TagStats {
+    pub trade_count: usize,
+    /// Fraction of the trades with a positive net P&L
+    pub win_rate: f64,
+    /// Net P&L summed over the trades
+    pub total_pl: f64,
+    /// Gross profit over gross loss, infinite when nothing lost
+    pub profit_factor: f64,
+}
\ No newline at end of file
@@ -0,0 +1,1200 @@
+use super::*;
+use crate::strategy::BaseStrategy;
+use crate::test_util::{bars, flat_bars};
+use chrono::Duration;
+use std::sync::Mutex;
+
+/// Strategy that hands each bar to a closure
+struct Scripted<F>(F);
+
+impl<F> Strategy for Scripted<F>
+where
+    F: FnMut(&mut Context, &OHLCV, usize) -> Vec<Order>,
+{
+    fn init(&mut self, _data: &[OHLCV]) -> Result<()> {
+        Ok(())
+    }
+
+    fn next_with_context(
+        &mut self,
+        ctx: &mut Context,
+        bar: &OHLCV,
+        index: usize,
+    ) -> Result<Vec<Order>> {
+        Ok((self.0)(ctx, bar, index))
+    }
+}
+
+fn market(side: OrderSide, size: f64) -> Order {
+    Order::new(side, OrderType::Market, size, None, None, None, None, None)
+}
+
+/// Position size on the bar after `order` was submitted on the first one
+fn size_after_fill(order: Order, config: BacktestConfig) -> f64 {
+    let data = flat_bars(&[100.0, 100.0, 100.0]);
+    let mut order = Some(order);
+    let mut size = 0.0;
+    Backtest::new(&data, config)
+        .run(Scripted(|ctx: &mut Context, _: &OHLCV, index| {
+            if index == 1 {
+                size = ctx.position_size();
+            }
+            order.take().into_iter().collect()
+        }))
+        .unwrap();
+    size
+}
+
+#[test]
+fn equity_fraction_order_deploys_that_share_of_equity() {
+    let order = market(OrderSide::Buy, 0.0).with_equity_fraction(0.5);
+    let size = size_after_fill(order, BacktestConfig::default());
+    assert!((size - 50.0).abs() < 1e-9, "{size}");
+}
+
+#[test]
+fn buy_without_a_size_deploys_nearly_all_equity() {
+    let order = BaseStrategy::new("base").buy(None, None, None, None, None, None);
+    let size = size_after_fill(order, BacktestConfig::default());
+    assert!((size - 99.99).abs() < 1e-9, "{size}");
+}
+
+#[test]
+fn buy_with_a_fractional_size_deploys_that_share_of_equity() {
+    let order = BaseStrategy::new("base").buy(Some(0.5), None, None, None, None, None);
+    let size = size_after_fill(order, BacktestConfig::default());
+    assert!((size - 50.0).abs() < 1e-9, "{size}");
+
+    let order = BaseStrategy::new("base").buy(Some(2.0), None, None, None, None, None);
+    assert_eq!(size_after_fill(order, BacktestConfig::default()), 2.0);
+}
+
+#[test]
+fn order_sizes_count_units_unless_sized_by_equity() {
+    for units in [0.5, 1.0, 2.0] {
+        let size = size_after_fill(market(OrderSide::Buy, units), BacktestConfig::default());
+        assert_eq!(size, units);
+    }
+}
+
+/// Rises for two bars after the entry, then pulls back through the 5% trailing stop
+fn trailing_stop_series() -> Vec<OHLCV> {
+    bars(&[
+        (100.0, 105.0, 99.0, 100.0),
+        (100.0, 110.0, 99.5, 108.0),
+        (108.0, 120.0, 107.0, 118.0),
+        (118.0, 119.0, 112.0, 113.0),
+        (113.0, 114.0, 111.0, 112.0),
+    ])
+}
+
+#[test]
+fn trailing_stop_ratchets_up_on_a_rising_series() {
+    let data = trailing_stop_series();
+    let mut stops = Vec::new();
+    Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(|ctx: &mut Context, _: &OHLCV, index| {
+            stops.push(ctx.position().and_then(|p| p.sl));
+            match index {
+                0 => vec![BaseStrategy::new("trail").buy_trailing(Some(10.0), 0.05, None, None)],
+                _ => vec![],
+            }
+        }))
+        .unwrap();
+
+    // Seeded from the 100 fill rather than the entry bar's 105 high, then 5% under
+    // each later high
+    assert_eq!(stops[1], Some(95.0));
+    assert_eq!(stops[2], Some(104.5));
+}
+
+#[test]
+fn trailing_stop_triggers_on_the_first_pullback() {
+    let data = trailing_stop_series();
+    let results = Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            0 => vec![BaseStrategy::new("trail").buy_trailing(Some(10.0), 0.05, None, None)],
+            _ => vec![],
+        }))
+        .unwrap();
+
+    assert_eq!(results.trades.len(), 1);
+    let trade = &results.trades[0];
+    assert_eq!(trade.entry_price, 100.0);
+    assert_eq!(trade.exit_bar, Some(3));
+    assert_eq!(trade.exit_price, Some(0.95 * 120.0));
+}
+
+/// Two round trips with a stop loss 50 under the entry: 100 -> 110, then 100 -> 90
+fn round_trips(_ctx: &mut Context, bar: &OHLCV, index: usize) -> Vec<Order> {
+    match index {
+        0 | 2 => vec![BaseStrategy::new("trips").buy(
+            Some(1.0),
+            None,
+            None,
+            Some(bar.close - 50.0),
+            None,
+            None,
+        )],
+        1 | 3 => vec![Order::flatten()],
+        _ => vec![],
+    }
+}
+
+#[test]
+fn trade_sink_streams_trades_and_keeps_aggregates() {
+    let data = flat_bars(&[100.0, 110.0, 100.0, 90.0, 90.0]);
+    let buffer = Arc::new(Mutex::new(Vec::<u8>::new()));
+    let lines = |buffer: &Mutex<Vec<u8>>| {
+        buffer
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count()
+    };
+
+    let config = BacktestConfig {
+        trade_sink: Some(TradeSink::from_shared(buffer.clone())),
+        ..Default::default()
+    };
+    let mut streamed = Vec::new();
+    let results = Backtest::new(&data, config)
+        .run(Scripted(|ctx: &mut Context, bar: &OHLCV, index| {
+            streamed.push(lines(&buffer));
+            round_trips(ctx, bar, index)
+        }))
+        .unwrap();
+
+    // Each trade is written as it closes rather than at the end
+    assert_eq!(streamed, [0, 0, 1, 1, 2]);
+    assert!(results.trades.is_empty());
+    assert!(results.r_multiples.is_empty());
+    assert_eq!(results.trade_count, 2);
+
+    let retained = Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(round_trips))
+        .unwrap();
+    assert_eq!(retained.r_multiples, [0.2, -0.2]);
+    assert_eq!(results.avg_r, retained.avg_r);
+    assert_eq!(results.expectancy_r, retained.expectancy_r);
+    assert_eq!(results.sqn, retained.sqn);
+}
+
+/// A one-day trade followed by a three-day one
+fn one_and_three_day_trades(config: BacktestConfig) -> BacktestResults {
+    let data = flat_bars(&[100.0; 6]);
+    Backtest::new(&data, config)
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            0 | 2 => vec![market(OrderSide::Buy, 1.0)],
+            1 | 5 => vec![Order::flatten()],
+            _ => vec![],
+        }))
+        .unwrap()
+}
+
+#[test]
+fn trade_durations_cover_every_closed_trade() {
+    let results = one_and_three_day_trades(BacktestConfig::default());
+
+    assert_eq!(results.trades.len(), 2);
+    assert_eq!(results.max_trade_duration, Duration::days(3));
+    assert_eq!(results.avg_trade_duration, Duration::days(2));
+}
+
+#[test]
+fn trade_durations_survive_a_trade_sink() {
+    let config = BacktestConfig {
+        trade_sink: Some(TradeSink::new(std::io::sink())),
+        ..Default::default()
+    };
+    let results = one_and_three_day_trades(config);
+
+    assert!(results.trades.is_empty());
+    assert_eq!(results.max_trade_duration, Duration::days(3));
+    assert_eq!(results.avg_trade_duration, Duration::days(2));
+}
+
+/// Enters every ten bars with a trailing stop and exits five bars later
+fn every_ten_bars(ctx: &mut Context, bar: &OHLCV, index: usize) -> Vec<Order> {
+    ctx.record_indicator("close", bar.close);
+    match index % 10 {
+        0 => vec![BaseStrategy::new("ten").buy_trailing(Some(20.0), 0.03, None, None)],
+        5 => vec![Order::flatten()],
+        _ => vec![],
+    }
+}
+
+#[test]
+fn resuming_from_a_checkpoint_matches_an_uninterrupted_run() {
+    let data = crate::data::DataLoader::create_sample_data_seeded(7, 60, 100.0);
+    let config = BacktestConfig {
+        commission: 0.001,
+        ..Default::default()
+    };
+
+    let uninterrupted = Backtest::new(&data, config.clone())
+        .run(Scripted(every_ten_bars))
+        .unwrap();
+
+    let mut strategy = Scripted(every_ten_bars);
+    let mut first = Backtest::new(&data, config.clone());
+    first.start(&mut strategy).unwrap();
+    for _ in 0..27 {
+        first.step(&mut strategy).unwrap();
+    }
+    // Results taken mid-run must not disturb the state being checkpointed
+    first.finalize().unwrap();
+    let saved = serde_json::to_string(&first.checkpoint()).unwrap();
+
+    let mut strategy = Scripted(every_ten_bars);
+    let mut resumed = Backtest::new(&data, config);
+    resumed.start(&mut strategy).unwrap();
+    resumed
+        .restore(serde_json::from_str(&saved).unwrap())
+        .unwrap();
+    while resumed.step(&mut strategy).unwrap() {}
+    let results = resumed.finalize().unwrap();
+
+    assert!(uninterrupted.trade_count > 2);
+    assert_eq!(
+        serde_json::to_value(&results).unwrap(),
+        serde_json::to_value(&uninterrupted).unwrap()
+    );
+}
+
+#[test]
+fn exits_pay_the_symbol_commission_override() {
+    let data = bars(&[
+        (100.0, 100.0, 100.0, 100.0),
+        (100.0, 101.0, 90.0, 92.0),
+        (92.0, 92.0, 92.0, 92.0),
+        (92.0, 92.0, 92.0, 92.0),
+    ]);
+    let mut config = BacktestConfig::default();
+    let btc_fee: Arc<dyn CommissionModel> = Arc::new(Percentage { rate: 0.01 });
+    config
+        .commission_overrides
+        .insert("BTC".to_string(), btc_fee);
+
+    let results = Backtest::new(&data, config)
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| {
+            let buy = || market(OrderSide::Buy, 10.0).with_symbol("BTC");
+            match index {
+                // The stop loss is hit on the next bar; the flatten order names no symbol
+                0 => {
+                    let mut order = buy();
+                    order.sl = Some(95.0);
+                    vec![order]
+                }
+                2 => vec![buy()],
+                3 => vec![Order::flatten()],
+                _ => vec![],
+            }
+        }))
+        .unwrap();
+
+    let exit_commissions: Vec<f64> = results.trades.iter().map(|t| t.exit_commission).collect();
+    assert_eq!(exit_commissions, [0.01 * 10.0 * 95.0, 0.01 * 10.0 * 92.0]);
+}
+
+/// Half a dollar against buys, nothing against sells
+#[derive(Debug)]
+struct BuySideSpread;
+
+impl SlippageModel for BuySideSpread {
+    fn slippage(&self, _price: f64, _size: f64, _bar: &OHLCV) -> f64 {
+        0.5
+    }
+
+    fn adjust_fill(&self, side: OrderSide, price: f64, size: f64, bar: &OHLCV) -> f64 {
+        match side {
+            OrderSide::Buy => price + self.slippage(price, size, bar),
+            OrderSide::Sell => price,
+        }
+    }
+}
+
+#[test]
+fn fills_go_through_a_custom_slippage_model() {
+    let data = flat_bars(&[100.0, 100.0, 100.0]);
+    let config = BacktestConfig::builder()
+        .slippage(FixedBps { bps: 100.0 })
+        .slippage_override("SPY", BuySideSpread)
+        .build()
+        .unwrap();
+
+    let results = Backtest::new(&data, config)
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            0 => vec![market(OrderSide::Buy, 10.0).with_symbol("SPY")],
+            1 => vec![market(OrderSide::Sell, 10.0).with_symbol("SPY")],
+            _ => vec![],
+        }))
+        .unwrap();
+
+    let trade = &results.trades[0];
+    assert_eq!(trade.entry_price, 100.5);
+    assert_eq!(trade.exit_price, Some(100.0));
+}
+
+/// Ten units bought at 100 that close January at 110 and February at 105
+fn fee_run(performance_fee: Option<PerformanceFee>) -> BacktestResults {
+    let prices: Vec<f64> = (0..40)
+        .map(|i| match i {
+            0 => 100.0,
+            1..=29 => 110.0,
+            _ => 105.0,
+        })
+        .collect();
+    let config = BacktestConfig {
+        performance_fee,
+        ..BacktestConfig::default()
+    };
+
+    Backtest::new(&flat_bars(&prices), config)
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| {
+            if index == 0 {
+                vec![market(OrderSide::Buy, 10.0)]
+            } else {
+                vec![]
+            }
+        }))
+        .unwrap()
+}
+
+#[test]
+fn performance_fee_is_charged_only_above_the_high_water_mark() {
+    let gross = fee_run(None);
+    let net = fee_run(Some(PerformanceFee {
+        rate: 0.2,
+        period: CalendarPeriod::Month,
+    }));
+
+    // January gains 100 over the 10,000 mark; February ends below the new mark
+    let fees: Vec<&LedgerEntry> = net
+        .ledger
+        .iter()
+        .filter(|entry| entry.event == LedgerEventType::PerformanceFee)
+        .collect();
+    assert_eq!(fees.len(), 1);
+    assert!((fees[0].amount + 20.0).abs() < 1e-9, "{}", fees[0].amount);
+    assert_eq!(fees[0].timestamp.date_naive().to_string(), "2023-01-31");
+
+    assert!((gross.equity_final - 10_050.0).abs() < 1e-9);
+    assert!((net.equity_final - 10_030.0).abs() < 1e-9);
+    assert!(net.equity_final < gross.equity_final);
+}
+
+/// Position size at the last bar when a buy needing two bars of confirmation is
+/// signalled on `signal_bars`
+fn size_after_confirmed_signal(signal_bars: &[usize]) -> f64 {
+    let data = flat_bars(&[100.0; 6]);
+    let mut size = 0.0;
+    Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(|ctx: &mut Context, _: &OHLCV, index| {
+            size = ctx.position_size();
+            if signal_bars.contains(&index) {
+                vec![market(OrderSide::Buy, 10.0).with_confirm_bars(2)]
+            } else {
+                vec![]
+            }
+        }))
+        .unwrap();
+    size
+}
+
+#[test]
+fn a_one_bar_blip_never_confirms() {
+    assert_eq!(size_after_confirmed_signal(&[1]), 0.0);
+    assert_eq!(size_after_confirmed_signal(&[1, 3]), 0.0);
+}
+
+#[test]
+fn a_sustained_signal_confirms_and_fills_once() {
+    assert_eq!(size_after_confirmed_signal(&[1, 2]), 10.0);
+}
+
+#[test]
+fn trade_on_open_fills_at_the_next_bar_open() {
+    let data = bars(&[
+        (100.0, 102.0, 99.0, 101.0),
+        (104.0, 106.0, 103.0, 105.0),
+        (107.0, 109.0, 106.0, 108.0),
+        (110.0, 110.0, 110.0, 110.0),
+    ]);
+    let config = BacktestConfig {
+        trade_on_open: true,
+        ..BacktestConfig::default()
+    };
+
+    let results = Backtest::new(&data, config)
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            0 => vec![market(OrderSide::Buy, 10.0)],
+            1 => vec![market(OrderSide::Sell, 10.0)],
+            _ => vec![],
+        }))
+        .unwrap();
+
+    let trade = &results.trades[0];
+    assert_eq!(trade.entry_price, 104.0);
+    assert_eq!(trade.exit_price, Some(107.0));
+}
+
+/// Engine with $10,000 and a 0.1% commission, ready to fill at 100
+fn engine_for_fills(data: &[OHLCV], partial_fills: PartialFills) -> Backtest<'_> {
+    let config = BacktestConfig {
+        commission: 0.001,
+        partial_fills,
+        ..BacktestConfig::default()
+    };
+    Backtest::new(data, config)
+}
+
+#[test]
+fn oversized_order_fills_what_buying_power_allows_and_returns_the_rest() {
+    let data = flat_bars(&[100.0]);
+    let mut engine = engine_for_fills(&data, PartialFills::CancelRemainder);
+    let order = market(OrderSide::Buy, 150.0);
+
+    let remainder = engine
+        .open_position(&order, OrderSide::Buy, 150.0, 100.0, &data[0])
+        .unwrap();
+
+    // Each unit costs 100 plus 0.1 commission
+    let affordable = 10_000.0 / 100.1;
+    assert!((engine.positions[0].size - affordable).abs() < 1e-6);
+    assert!(
+        (remainder - (150.0 - affordable)).abs() < 1e-6,
+        "{remainder}"
+    );
+    assert!(engine.cash.abs() < 1e-6, "{}", engine.cash);
+}
+
+#[test]
+fn oversized_order_is_rejected_whole_by_default() {
+    let data = flat_bars(&[100.0]);
+    let mut engine = engine_for_fills(&data, PartialFills::Reject);
+    let order = market(OrderSide::Buy, 150.0);
+
+    let remainder = engine
+        .open_position(&order, OrderSide::Buy, 150.0, 100.0, &data[0])
+        .unwrap();
+
+    assert_eq!(remainder, 150.0);
+    assert!(engine.positions.is_empty());
+    assert_eq!(engine.cash, 10_000.0);
+}
+
+/// `(position size, open orders)` seen on each bar when 150 units are bought with
+/// room for 100 and any holding is sold again on the third bar
+fn oversized_buy_then_sell(partial_fills: PartialFills) -> Vec<(f64, usize)> {
+    let data = flat_bars(&[100.0; 5]);
+    let config = BacktestConfig {
+        partial_fills,
+        ..BacktestConfig::default()
+    };
+    let mut seen = Vec::new();
+    Backtest::new(&data, config)
+        .run(Scripted(|ctx: &mut Context, _: &OHLCV, index| {
+            seen.push((ctx.position_size(), ctx.open_orders().len()));
+            match index {
+                0 => vec![market(OrderSide::Buy, 150.0)],
+                2 if ctx.position_size() > 0.0 => vec![market(OrderSide::Sell, 100.0)],
+                _ => vec![],
+            }
+        }))
+        .unwrap();
+    seen
+}
+
+#[test]
+fn reject_drops_an_order_beyond_buying_power() {
+    let seen = oversized_buy_then_sell(PartialFills::Reject);
+    assert!(seen.iter().all(|&step| step == (0.0, 0)), "{seen:?}");
+}
+
+#[test]
+fn rest_remainder_fills_the_rest_once_buying_power_frees_up() {
+    let seen = oversized_buy_then_sell(PartialFills::RestRemainder);
+    assert_eq!(
+        seen,
+        [(0.0, 0), (100.0, 1), (100.0, 1), (50.0, 0), (50.0, 0)]
+    );
+}
+
+/// Units held after deploying all of $10,000 into a $97 stock with 0.1% commission
+fn units_bought_at_97(fractional: bool, order: Order) -> f64 {
+    let data = flat_bars(&[97.0, 97.0, 97.0]);
+    let config = BacktestConfig {
+        commission: 0.001,
+        partial_fills: PartialFills::CancelRemainder,
+        fractional,
+        ..BacktestConfig::default()
+    };
+    let mut order = Some(order);
+    let mut size = 0.0;
+    Backtest::new(&data, config)
+        .run(Scripted(|ctx: &mut Context, _: &OHLCV, index| {
+            if index == 1 {
+                size = ctx.position_size();
+            }
+            order.take().into_iter().collect()
+        }))
+        .unwrap();
+    size
+}
+
+#[test]
+fn fills_are_whole_units_when_fractional_is_off() {
+    let by_equity = || market(OrderSide::Buy, 0.0).with_equity_fraction(1.0);
+    let oversized = || market(OrderSide::Buy, 150.0);
+
+    assert_eq!(units_bought_at_97(false, by_equity()), 102.0);
+    assert_eq!(units_bought_at_97(false, oversized()), 102.0);
+    assert_eq!(units_bought_at_97(false, market(OrderSide::Buy, 2.7)), 2.0);
+
+    // The $10 commission comes out of the budget
+    let fractional = units_bought_at_97(true, by_equity());
+    assert!((fractional - 9_990.0 / 97.0).abs() < 1e-6, "{fractional}");
+}
+
+/// Buys 10 and sells 5 on the first bar at 100, then runs `later` once the price is 110
+fn long_and_short_legs(
+    hedging: bool,
+    mut later: impl FnMut(&mut Context, usize) -> Vec<Order>,
+) -> BacktestResults {
+    let data = flat_bars(&[100.0, 110.0, 110.0, 110.0]);
+    let config = BacktestConfig {
+        hedging,
+        ..Default::default()
+    };
+    Backtest::new(&data, config)
+        .run(Scripted(
+            |ctx: &mut Context, _: &OHLCV, index| match index {
+                0 => vec![market(OrderSide::Buy, 10.0), market(OrderSide::Sell, 5.0)],
+                _ => later(ctx, index),
+            },
+        ))
+        .unwrap()
+}
+
+#[test]
+fn hedged_long_and_short_both_count_towards_equity() {
+    let mut legs = Vec::new();
+    let results = long_and_short_legs(true, |ctx, _| {
+        legs = ctx.positions().iter().map(|p| p.size).collect();
+        Vec::new()
+    });
+
+    assert_eq!(legs, [10.0, -5.0]);
+    // 9000 of cash, the long worth 1100 and the short 50 under water
+    assert!((results.equity_final - 10_050.0).abs() < 1e-9);
+
+    // Netting the same orders into a 5 unit long is worth the same
+    let netted = long_and_short_legs(false, |_, _| Vec::new());
+    assert!((netted.equity_final - results.equity_final).abs() < 1e-9);
+}
+
+#[test]
+fn closing_a_position_by_id_leaves_the_other_leg_open() {
+    let mut legs = Vec::new();
+    let results = long_and_short_legs(true, |ctx, index| {
+        legs = ctx.positions().iter().map(|p| p.size).collect();
+        match index {
+            1 => {
+                let long = ctx.positions().iter().find(|p| p.is_long()).unwrap();
+                ctx.close_position_by_id(long.id, 1.0);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    });
+
+    assert_eq!(legs, [-5.0]);
+    assert_eq!(results.trades.len(), 1);
+    assert!((results.trades[0].pl() - 100.0).abs() < 1e-9);
+    // The long's 1100 of proceeds back in cash, the short still 50 under water
+    assert!((results.equity_final - 10_050.0).abs() < 1e-9);
+}
+
+#[test]
+fn orders_beyond_the_margin_buying_power_are_rejected() {
+    // 2:1 leverage turns 10000 of equity into 20000 of buying power at 100 a unit
+    let config = || BacktestConfig {
+        margin: 0.5,
+        ..Default::default()
+    };
+    assert_eq!(
+        size_after_fill(market(OrderSide::Buy, 300.0), config()),
+        0.0
+    );
+    assert_eq!(
+        size_after_fill(market(OrderSide::Buy, 150.0), config()),
+        150.0
+    );
+}
+
+#[test]
+fn falling_below_maintenance_margin_liquidates_at_the_close() {
+    let data = flat_bars(&[100.0, 95.0, 90.0, 85.0]);
+    let config = BacktestConfig {
+        margin: 0.25,
+        maintenance_margin: 0.2,
+        ..Default::default()
+    };
+    let results = Backtest::new(&data, config)
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            0 => vec![market(OrderSide::Buy, 400.0)],
+            _ => Vec::new(),
+        }))
+        .unwrap();
+
+    // Equity of 400p - 30000 covers 20% of the 400p position down to 93.75, so the
+    // close at 95 holds and the close at 90 liquidates
+    assert_eq!(results.trades.len(), 1);
+    let trade = &results.trades[0];
+    assert_eq!(trade.exit_bar, Some(2));
+    assert_eq!(trade.exit_price, Some(90.0));
+    assert!((results.equity_final - 6_000.0).abs() < 1e-9);
+}
+
+/// Scales in over two bars, flattens both lots, then re-enters into a stop loss,
+/// noting every trade the hook reports
+struct TradeCounter<'a>(&'a mut Vec<(usize, Option<usize>)>);
+
+impl Strategy for TradeCounter<'_> {
+    fn init(&mut self, _data: &[OHLCV]) -> Result<()> {
+        Ok(())
+    }
+
+    fn next(&mut self, bar: &OHLCV, index: usize) -> Result<Vec<Order>> {
+        Ok(match index {
+            0 | 1 => vec![market(OrderSide::Buy, 1.0)],
+            2 => vec![Order::flatten()],
+            3 => vec![Order::new(
+                OrderSide::Buy,
+                OrderType::Market,
+                1.0,
+                None,
+                None,
+                Some(bar.close - 5.0),
+                None,
+                None,
+            )],
+            _ => Vec::new(),
+        })
+    }
+
+    fn on_trade_close(&mut self, trade: &Trade) -> Result<()> {
+        self.0.push((trade.entry_bar, trade.exit_bar));
+        Ok(())
+    }
+}
+
+#[test]
+fn trade_hook_fires_once_per_closed_trade() {
+    let data = flat_bars(&[100.0, 100.0, 100.0, 100.0, 90.0, 90.0]);
+    let mut reported = Vec::new();
+    let results = Backtest::new(&data, BacktestConfig::default())
+        .run(TradeCounter(&mut reported))
+        .unwrap();
+
+    let closed: Vec<_> = results
+        .trades
+        .iter()
+        .map(|t| (t.entry_bar, t.exit_bar))
+        .collect();
+    assert_eq!(closed, [(0, Some(2)), (1, Some(2)), (3, Some(4))]);
+    assert_eq!(reported, closed);
+}
+
+/// Net position size after each bar of selling 10 units, then buying 5 twice
+fn sell_then_buys(exclusive_orders: bool) -> Vec<f64> {
+    let data = flat_bars(&[100.0; 4]);
+    let config = BacktestConfig {
+        exclusive_orders,
+        ..Default::default()
+    };
+    let mut sizes = Vec::new();
+    Backtest::new(&data, config)
+        .run(Scripted(|ctx: &mut Context, _: &OHLCV, index| {
+            if index > 0 {
+                sizes.push(ctx.position_size());
+            }
+            match index {
+                0 => vec![market(OrderSide::Sell, 10.0)],
+                1 | 2 => vec![market(OrderSide::Buy, 5.0)],
+                _ => Vec::new(),
+            }
+        }))
+        .unwrap();
+    sizes
+}
+
+#[test]
+fn exclusive_orders_flip_the_position_instead_of_stacking() {
+    assert_eq!(sell_then_buys(true), [-10.0, 5.0, 5.0]);
+    assert_eq!(sell_then_buys(false), [-10.0, -5.0, 0.0]);
+}
+
+#[test]
+fn overlapping_trades_count_each_exposed_bar_once() {
+    let data = flat_bars(&[100.0; 10]);
+    let results = Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            1 | 2 => vec![market(OrderSide::Buy, 1.0)],
+            4 => vec![Order::close_portion(0.5)],
+            6 => vec![Order::flatten()],
+            _ => Vec::new(),
+        }))
+        .unwrap();
+
+    // Trades over bars 1-4 and 2-6 overlap; together they hold bars 1 through 6
+    assert_eq!(results.trades.len(), 2);
+    assert!((results.exposure_time - 0.6).abs() < 1e-12);
+}
+
+#[test]
+fn intraday_exposure_counts_bars_not_days() {
+    let mut data = flat_bars(&[100.0; 8]);
+    let start = data[0].timestamp;
+    for (i, bar) in data.iter_mut().enumerate() {
+        bar.timestamp = start + Duration::hours(i as i64);
+    }
+    let results = Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            1 => vec![market(OrderSide::Buy, 1.0)],
+            2 => vec![Order::flatten()],
+            _ => Vec::new(),
+        }))
+        .unwrap();
+
+    // An hour-long trade holds the bar it opened on and the one it closed on
+    assert!((results.exposure_time - 0.25).abs() < 1e-12);
+}
+
+#[test]
+fn close_flattens_a_long_without_going_short() {
+    let data = flat_bars(&[100.0; 4]);
+    let mut sizes = Vec::new();
+    let results = Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(|ctx: &mut Context, _: &OHLCV, index| {
+            sizes.push(ctx.position_size());
+            match index {
+                0 => vec![market(OrderSide::Buy, 10.0)],
+                1 => vec![BaseStrategy::new("close").close()],
+                _ => Vec::new(),
+            }
+        }))
+        .unwrap();
+
+    assert_eq!(sizes, [0.0, 10.0, 0.0, 0.0]);
+    assert_eq!(results.trades.len(), 1);
+    assert!(results.trades[0].is_long());
+}
+
+#[test]
+fn ledger_reconciles_with_cash_and_final_equity() {
+    let data = flat_bars(&[100.0, 104.0, 98.0, 101.0, 107.0]);
+    let config = BacktestConfig {
+        commission: 0.001,
+        ..Default::default()
+    };
+    let mut strategy = Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+        0 => vec![market(OrderSide::Buy, 30.0)],
+        2 => vec![Order::close_portion(0.5)],
+        3 => vec![market(OrderSide::Buy, 10.0)],
+        _ => Vec::new(),
+    });
+    let mut engine = Backtest::new(&data, config);
+    engine.start(&mut strategy).unwrap();
+    while engine.step(&mut strategy).unwrap() {}
+    let results = engine.finalize().unwrap();
+
+    let last = results.ledger.last().unwrap();
+    let posted: f64 = results.ledger.iter().map(|entry| entry.amount).sum();
+    assert!((last.cash - engine.cash).abs() < 1e-9);
+    assert!((results.initial_equity + posted - engine.cash).abs() < 1e-9);
+
+    // The 25 units still open are marked at the last close
+    let open_value: f64 = engine.positions.iter().map(Position::value).sum();
+    assert!((open_value - 25.0 * 107.0).abs() < 1e-9);
+    assert!((results.equity_final - (last.cash + open_value)).abs() < 1e-9);
+}
+
+/// Exit price of a long with a stop at 95 and a target at 105 when the next bar
+/// spans both
+fn exit_on_a_bar_hitting_both_levels(intrabar_path: IntrabarPath) -> Option<f64> {
+    let data = bars(&[
+        (100.0, 100.0, 100.0, 100.0),
+        (100.0, 106.0, 94.0, 100.0),
+        (100.0, 100.0, 100.0, 100.0),
+    ]);
+    let config = BacktestConfig {
+        intrabar_path,
+        ..Default::default()
+    };
+    let results = Backtest::new(&data, config)
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            0 => vec![Order::new(
+                OrderSide::Buy,
+                OrderType::Market,
+                1.0,
+                None,
+                None,
+                Some(95.0),
+                Some(105.0),
+                None,
+            )],
+            _ => Vec::new(),
+        }))
+        .unwrap();
+    results.trades.first().and_then(|trade| trade.exit_price)
+}
+
+#[test]
+fn high_first_and_low_first_paths_resolve_a_two_sided_bar_differently() {
+    assert_eq!(
+        exit_on_a_bar_hitting_both_levels(IntrabarPath::OHLC),
+        Some(105.0)
+    );
+    assert_eq!(
+        exit_on_a_bar_hitting_both_levels(IntrabarPath::OLHC),
+        Some(95.0)
+    );
+}
+
+#[test]
+fn signal_and_fill_on_open_shows_only_the_open_and_fills_there() {
+    let data = bars(&[
+        (100.0, 103.0, 99.0, 102.0),
+        (104.0, 108.0, 103.0, 107.0),
+        (107.0, 109.0, 105.0, 106.0),
+    ]);
+    let config = BacktestConfig {
+        fill_timing: FillTiming::SignalAndFillOnOpen,
+        ..Default::default()
+    };
+    let mut seen = Vec::new();
+    let results = Backtest::new(&data, config)
+        .run(Scripted(|_: &mut Context, bar: &OHLCV, index| {
+            seen.push(bar.clone());
+            match index {
+                1 => vec![market(OrderSide::Buy, 1.0)],
+                2 => vec![Order::flatten()],
+                _ => Vec::new(),
+            }
+        }))
+        .unwrap();
+
+    for (seen, bar) in seen.iter().zip(&data) {
+        assert_eq!(seen.timestamp, bar.timestamp);
+        assert_eq!(seen.open, bar.open);
+        assert!(seen.high.is_nan() && seen.low.is_nan() && seen.close.is_nan());
+        assert!(seen.volume.is_nan());
+    }
+    let trade = &results.trades[0];
+    assert_eq!(trade.entry_price, 104.0);
+    assert_eq!(trade.exit_price, Some(107.0));
+}
+
+/// Financing charged over ten days on `units` bought at 100 with 10000 of cash
+fn financing_charged(units: f64, margin: f64) -> f64 {
+    let data = flat_bars(&[100.0; 11]);
+    let config = BacktestConfig {
+        margin,
+        financing_rate: 0.05,
+        ..Default::default()
+    };
+    let results = Backtest::new(&data, config)
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            0 => vec![market(OrderSide::Buy, units)],
+            _ => Vec::new(),
+        }))
+        .unwrap();
+    -results
+        .ledger
+        .iter()
+        .filter(|entry| entry.event == LedgerEventType::Financing)
+        .map(|entry| entry.amount)
+        .sum::<f64>()
+}
+
+#[test]
+fn a_leveraged_long_pays_financing_on_the_borrowed_half() {
+    // A 2x long borrows 10000 at 5% a year for ten days
+    let expected = 10_000.0 * 0.05 * 10.0 / 365.25;
+    let charged = financing_charged(200.0, 0.5);
+    assert!((charged - expected).abs() < 0.02, "{charged} vs {expected}");
+
+    assert_eq!(financing_charged(100.0, 1.0), 0.0);
+}
+
+/// Interest earned over 20 days at 4% a year, with all the cash spent on bar
+/// `invest_at` when given
+fn interest_earned(invest_at: Option<usize>) -> f64 {
+    let data = flat_bars(&[100.0; 21]);
+    let config = BacktestConfig {
+        cash_interest_rate: 0.04,
+        ..Default::default()
+    };
+    let results = Backtest::new(&data, config)
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| {
+            match Some(index) == invest_at {
+                true => vec![market(OrderSide::Buy, 100.0)],
+                false => Vec::new(),
+            }
+        }))
+        .unwrap();
+    results
+        .ledger
+        .iter()
+        .filter(|entry| entry.event == LedgerEventType::Interest)
+        .map(|entry| entry.amount)
+        .sum()
+}
+
+#[test]
+fn cash_held_for_half_the_period_earns_about_half_the_interest() {
+    // Credited daily, so the interest compounds
+    let full = interest_earned(None);
+    let compounded = 10_000.0 * ((1.0 + 0.04 / 365.25_f64).powi(20) - 1.0);
+    assert!((full - compounded).abs() < 1e-9, "{full}");
+
+    let half = interest_earned(Some(10));
+    assert!((half / full - 0.5).abs() < 0.01, "{half} vs {full}");
+}
+
+/// Price paid above the 100 close for `units` under square-root market impact
+fn impact_paid(units: f64) -> f64 {
+    let data = flat_bars(&[100.0; 2]);
+    let config = BacktestConfig {
+        initial_cash: 100_000.0,
+        slippage: Arc::new(SquareRootImpact { k: 0.1 }),
+        ..Default::default()
+    };
+    let mut entry_price = 0.0;
+    Backtest::new(&data, config)
+        .run(Scripted(|ctx: &mut Context, _: &OHLCV, index| {
+            if let Some(position) = ctx.position() {
+                entry_price = position.entry_price;
+            }
+            match index {
+                0 => vec![market(OrderSide::Buy, units)],
+                _ => Vec::new(),
+            }
+        }))
+        .unwrap();
+    entry_price - 100.0
+}
+
+#[test]
+fn square_root_impact_grows_with_the_root_of_size() {
+    let (small, large) = (impact_paid(10.0), impact_paid(100.0));
+    // 10 of the bar's 1000 units moves the price by 0.1 * sqrt(0.01) of it
+    assert!((small - 1.0).abs() < 1e-9, "{small}");
+    assert!(
+        (large / small - 10f64.sqrt()).abs() < 1e-9,
+        "{large} vs {small}"
+    );
+}
+
+#[test]
+fn an_exit_at_two_and_a_half_times_the_risk_is_plus_two_and_a_half_r() {
+    let data = flat_bars(&[100.0, 110.0, 110.0]);
+    let results = Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            0 => vec![Order::new(
+                OrderSide::Buy,
+                OrderType::Market,
+                3.0,
+                None,
+                None,
+                Some(96.0),
+                None,
+                None,
+            )],
+            1 => vec![Order::flatten()],
+            _ => Vec::new(),
+        }))
+        .unwrap();
+
+    // Risking 4 a unit and exiting 10 up
+    assert_eq!(results.trades[0].r_multiple(), Some(2.5));
+    assert_eq!(results.r_multiples, [2.5]);
+    assert_eq!(results.avg_r, Some(2.5));
+}
+
+/// Length of a trade entered on Friday 2023-01-06 and exited the next Monday
+fn weekend_trade_length(duration_unit: DurationUnit) -> f64 {
+    let mut data = flat_bars(&[100.0; 4]);
+    // Thursday, Friday, Monday and Tuesday after the first bar's Monday
+    let monday = data[0].timestamp;
+    for (bar, days) in data.iter_mut().zip([3, 4, 7, 8]) {
+        bar.timestamp = monday + Duration::days(days);
+    }
+    let config = BacktestConfig {
+        duration_unit,
+        ..Default::default()
+    };
+    let results = Backtest::new(&data, config)
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            1 => vec![market(OrderSide::Buy, 1.0)],
+            2 => vec![Order::flatten()],
+            _ => Vec::new(),
+        }))
+        .unwrap();
+    results.max_trade_duration_units
+}
+
+#[test]
+fn a_friday_to_monday_trade_lasts_one_trading_day() {
+    assert_eq!(weekend_trade_length(DurationUnit::TradingDays), 1.0);
+    assert_eq!(weekend_trade_length(DurationUnit::Calendar), 3.0);
+}
+
+#[test]
+fn a_run_without_trades_reports_no_trade_statistics() {
+    let data = flat_bars(&[100.0, 101.0, 99.0]);
+    let results = Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(|_: &mut Context, _: &OHLCV, _| Vec::new()))
+        .unwrap();
+
+    assert_eq!(results.trade_count, 0);
+    assert_eq!(results.best_trade, None);
+    assert_eq!(results.worst_trade, None);
+    assert_eq!(results.avg_trade, None);
+    assert_eq!(results.expectancy, None);
+    assert_eq!(results.avg_r, None);
+    assert_eq!(results.expectancy_r, None);
+    assert_eq!(results.trade_stats.avg_win, None);
+    assert_eq!(results.trade_stats.largest_loss, None);
+}
+
+/// Random walk of daily closes starting at 100
+fn random_walk(bars: usize, seed: u64) -> Vec<OHLCV> {
+    use rand::{Rng, SeedableRng};
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let mut closes = vec![100.0];
+    for _ in 1..bars {
+        let step = 1.0 + rng.gen_range(-0.02..0.02);
+        closes.push(closes.last().unwrap() * step);
+    }
+    flat_bars(&closes)
+}
+
+#[test]
+fn buy_and_hold_has_a_rolling_beta_of_one() {
+    let data = random_walk(60, 3);
+    let results = Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            0 => vec![market(OrderSide::Buy, 100.0)],
+            _ => Vec::new(),
+        }))
+        .unwrap();
+
+    // All 10000 of cash is in the market from the first close on
+    let betas = results.rolling_beta(&data, 10);
+    assert_eq!(betas.len(), data.len() - 10);
+    for (timestamp, beta) in betas {
+        assert!((beta - 1.0).abs() < 1e-9, "{timestamp}: {beta}");
+    }
+    for (timestamp, correlation) in results.rolling_correlation(&data, 10) {
+        assert!(
+            (correlation - 1.0).abs() < 1e-9,
+            "{timestamp}: {correlation}"
+        );
+    }
+}
+
+#[test]
+fn lagging_a_rally_shows_a_tracking_drawdown_despite_gains() {
+    let closes: Vec<f64> = (0..=10).map(|i| 100.0 + 5.0 * i as f64).collect();
+    let data = flat_bars(&closes);
+    let results = Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(|_: &mut Context, _: &OHLCV, index| match index {
+            0 => vec![market(OrderSide::Buy, 50.0)],
+            _ => Vec::new(),
+        }))
+        .unwrap();
+
+    // Half invested, equity ends at 12500 while the benchmark goes from 100 to 150
+    assert!((results.return_pct - 0.25).abs() < 1e-9);
+    let relative_end = 1.25 / 1.5;
+    assert!((results.tracking_drawdown(&data) - (relative_end - 1.0)).abs() < 1e-9);
+}
+
+#[test]
+fn first_bar_of_the_month_fires_once_per_month() {
+    // Daily bars from 2023-01-02 to 2023-03-12, across two month boundaries
+    let data = flat_bars(&[100.0; 70]);
+    let mut firsts = Vec::new();
+    Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(|ctx: &mut Context, bar: &OHLCV, _| {
+            if ctx.is_first_bar_of(CalendarPeriod::Month) {
+                firsts.push(bar.timestamp.date_naive().to_string());
+            }
+            Vec::new()
+        }))
+        .unwrap();
+
+    assert_eq!(firsts, ["2023-01-02", "2023-02-01", "2023-03-01"]);
+}
+
+#[test]
+fn recorded_indicators_stay_aligned_to_the_bars_through_warmup() {
+    use crate::indicators::{Indicator, SimpleMovingAverage};
+
+    let data = flat_bars(&[10.0, 11.0, 12.0, 13.0, 14.0, 15.0]);
+    let mut sma = SimpleMovingAverage::new(3);
+    let results = Backtest::new(&data, BacktestConfig::default())
+        .run(Scripted(|ctx: &mut Context, bar: &OHLCV, _| {
+            if let Some(value) = sma.update(bar) {
+                ctx.record_indicator("sma", value);
+            }
+            Vec::new()
+        }))
+        .unwrap();
+
+    let series = &results.indicator_series["sma"];
+    let timestamps: Vec<_> = series.iter().map(|&(timestamp, _)| timestamp).collect();
+    let bar_times: Vec<_> = data.iter().map(|bar| bar.timestamp).collect();
+    assert_eq!(timestamps, bar_times);
+
+    let values: Vec<_> = series.iter().map(|&(_, value)| value).collect();
+    assert_eq!(
+        values,
+        [None, None, Some(11.0), Some(12.0), Some(13.0), Some(14.0)]
+    );
+}
+
+/// Strategy that needs 200 bars of history, like a 200-period SMA
+struct NeedsLongHistory;
+
+impl Strategy for NeedsLongHistory {
+    fn init(&mut self, _data: &[OHLCV]) -> Result<()> {
+        Ok(())
+    }
+
+    fn min_bars(&self) -> usize {
+        200
+    }
+}
+
+#[test]
+fn too_little_data_for_the_strategy_is_an_error() {
+    let data = flat_bars(&[100.0; 50]);
+    let error = Backtest::new(&data, BacktestConfig::default())
+        .run(NeedsLongHistory)
+        .unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<BacktestError>(),
+        Some(BacktestError::InsufficientData {
+            have: 50,
+            need: 200
+        })
+    ));
+    assert!(error.to_string().contains("200"), "{error}");
+}
\ No newline at end of file
@@ -0,0 +1,44 @@
+use crate::trade::Trade;
+use crate::Result;
+use std::fmt;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Destination that receives each closed trade as a JSON line.
+///
+/// When a sink is configured the engine writes trades out as they close instead of
+/// keeping them in `BacktestResults::trades`; aggregate statistics are still computed.
+#[derive(Clone)]
+pub struct TradeSink {
+    writer: Arc<Mutex<dyn Write + Send>>,
+}
+
+impl TradeSink {
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Self {
+        Self::from_shared(Arc::new(Mutex::new(writer)))
+    }
+
+    /// Use a writer the caller keeps a handle to, e.g. to inspect an in-memory buffer
+    pub fn from_shared<W: Write + Send + 'static>(writer: Arc<Mutex<W>>) -> Self {
+        Self { writer }
+    }
+
+    pub fn write_trade(&self, trade: &Trade) -> Result<()> {
+        let mut writer = self.writer.lock().map_err(|_| "trade sink lock poisoned")?;
+        serde_json::to_writer(&mut *writer, trade)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        let mut writer = self.writer.lock().map_err(|_| "trade sink lock poisoned")?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for TradeSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TradeSink").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Trade-level statistics over the closed trades, in the order they closed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradeStats {
+    /// Trades with a positive net P&L
+    pub wins: usize,
+    /// Trades with a negative net P&L
+    pub losses: usize,
+    /// Longest run of consecutive winning trades
+    pub max_consecutive_wins: usize,
+    /// Longest run of consecutive losing trades
+    pub max_consecutive_losses: usize,
+    /// Mean P&L of the winning trades, `None` without a win
+    pub avg_win: Option<f64>,
+    /// Mean P&L of the losing trades (negative), `None` without a loss
+    pub avg_loss: Option<f64>,
+    /// P&L of the largest winning trade, `None` without a win
+    pub largest_win: Option<f64>,
+    /// P&L of the largest losing trade (negative), `None` without a loss
+    pub largest_loss: Option<f64>,
+    /// Mean number of bars between entry and exit, `None` without a closed trade
+    pub avg_bars_held: Option<f64>,
+    /// Net `pl_pct` of every retained trade; empty when trades stream to a `trade_sink`
+    pub returns: Vec<f64>,
+}
@@ -0,0 +1,203 @@
+use super::{duration_seconds, TradeStats};
+use crate::trade::Trade;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+/// Running aggregates over closed trades, so results don't depend on keeping every trade
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct TradeSummary {
+    pub count: usize,
+    pub wins: usize,
+    pub total_pl: f64,
+    pub total_pl_squared: f64,
+    pub best: f64,
+    pub worst: f64,
+    pub gross_profit: f64,
+    pub gross_loss: f64,
+    /// Longest and total wall-clock time between entry and exit
+    #[serde(with = "duration_seconds")]
+    pub max_duration: Duration,
+    #[serde(with = "duration_seconds")]
+    pub total_duration: Duration,
+    pub max_duration_units: f64,
+    pub total_duration_units: f64,
+    pub duration_count: usize,
+    /// Trades with a stop loss, which have an R-multiple
+    pub r_count: usize,
+    pub r_wins: usize,
+    pub r_losses: usize,
+    pub r_win_total: f64,
+    /// Sum of the negative R-multiples, as a positive number
+    pub r_loss_total: f64,
+    pub losses: usize,
+    pub win_streak: usize,
+    pub loss_streak: usize,
+    pub max_win_streak: usize,
+    pub max_loss_streak: usize,
+    pub largest_win: Option<f64>,
+    pub largest_loss: Option<f64>,
+    pub total_bars_held: usize,
+    pub bars_held_count: usize,
+}
+
+impl TradeSummary {
+    pub fn record(&mut self, trade: &Trade, duration_units: Option<f64>) {
+        let pl = trade.pl();
+
+        // Seed the extremes from the first trade rather than from zero
+        if self.count == 0 {
+            self.best = pl;
+            self.worst = pl;
+        } else {
+            self.best = self.best.max(pl);
+            self.worst = self.worst.min(pl);
+        }
+        self.count += 1;
+        self.total_pl += pl;
+        self.total_pl_squared += pl * pl;
+        if pl > 0.0 {
+            self.wins += 1;
+            self.gross_profit += pl;
+            self.largest_win = Some(self.largest_win.map_or(pl, |best| best.max(pl)));
+        } else if pl < 0.0 {
+            self.losses += 1;
+            self.gross_loss += pl.abs();
+            self.largest_loss = Some(self.largest_loss.map_or(pl, |worst| worst.min(pl)));
+        }
+
+        // A breakeven trade ends both streaks
+        self.win_streak = if pl > 0.0 { self.win_streak + 1 } else { 0 };
+        self.loss_streak = if pl < 0.0 { self.loss_streak + 1 } else { 0 };
+        self.max_win_streak = self.max_win_streak.max(self.win_streak);
+        self.max_loss_streak = self.max_loss_streak.max(self.loss_streak);
+
+        if let Some(bars) = trade.duration_bars() {
+            self.total_bars_held += bars;
+            self.bars_held_count += 1;
+        }
+
+        let duration = trade.duration();
+        self.max_duration = self.max_duration.max(duration);
+        self.total_duration += duration;
+
+        if let Some(units) = duration_units {
+            self.max_duration_units = self.max_duration_units.max(units);
+            self.total_duration_units += units;
+            self.duration_count += 1;
+        }
+
+        if let Some(r) = trade.r_multiple() {
+            self.r_count += 1;
+            if r > 0.0 {
+                self.r_wins += 1;
+                self.r_win_total += r;
+            } else if r < 0.0 {
+                self.r_losses += 1;
+                self.r_loss_total += r.abs();
+            }
+        }
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.wins as f64 / self.count as f64
+        }
+    }
+
+    pub fn avg_pl(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.total_pl / self.count as f64
+        }
+    }
+
+    pub fn profit_factor(&self) -> f64 {
+        if self.gross_loss > 0.0 {
+            self.gross_profit / self.gross_loss
+        } else if self.gross_profit > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        }
+    }
+
+    /// System Quality Number over the trade P&Ls
+    pub fn sqn(&self) -> f64 {
+        if self.count < 2 {
+            return 0.0;
+        }
+
+        let n = self.count as f64;
+        let mean = self.total_pl / n;
+        let variance = (self.total_pl_squared / n - mean * mean).max(0.0);
+        let std_dev = variance.sqrt();
+
+        if std_dev > 0.0 {
+            n.sqrt() * mean / std_dev
+        } else {
+            0.0
+        }
+    }
+
+    /// Average R-multiple and R expectancy (win rate x average win R - loss rate x
+    /// average loss R) over the trades with a stop loss
+    pub fn r_expectancy(&self) -> (Option<f64>, Option<f64>) {
+        if self.r_count == 0 {
+            return (None, None);
+        }
+
+        let count = self.r_count as f64;
+        let avg_r = (self.r_win_total - self.r_loss_total) / count;
+        let avg_win = if self.r_wins > 0 {
+            self.r_win_total / self.r_wins as f64
+        } else {
+            0.0
+        };
+        let avg_loss = if self.r_losses > 0 {
+            self.r_loss_total / self.r_losses as f64
+        } else {
+            0.0
+        };
+        let expectancy_r =
+            self.r_wins as f64 / count * avg_win - self.r_losses as f64 / count * avg_loss;
+
+        (Some(avg_r), Some(expectancy_r))
+    }
+
+    /// Trade statistics with `returns` left empty for the caller to fill in from the
+    /// trades it retained
+    pub fn trade_stats(&self) -> TradeStats {
+        TradeStats {
+            wins: self.wins,
+            losses: self.losses,
+            max_consecutive_wins: self.max_win_streak,
+            max_consecutive_losses: self.max_loss_streak,
+            avg_win: (self.wins > 0).then(|| self.gross_profit / self.wins as f64),
+            avg_loss: (self.losses > 0).then(|| -self.gross_loss / self.losses as f64),
+            largest_win: self.largest_win,
+            largest_loss: self.largest_loss,
+            avg_bars_held: (self.bars_held_count > 0)
+                .then(|| self.total_bars_held as f64 / self.bars_held_count as f64),
+            returns: Vec::new(),
+        }
+    }
+
+    pub fn avg_duration(&self) -> Duration {
+        if self.count == 0 {
+            Duration::zero()
+        } else {
+            self.total_duration / self.count as i32
+        }
+    }
+
+    pub fn avg_duration_units(&self) -> f64 {
+        if self.duration_count == 0 {
+            0.0
+        } else {
+            self.total_duration_units / self.duration_count as f64
+        }
+    }
+}
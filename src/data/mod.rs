@@ -1,13 +1,13 @@
 use crate::types::OHLCV;
 use crate::Result;
-use chrono::{DateTime, Utc, NaiveDateTime};
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, TimeZone, Utc};
 use csv::Reader;
 use serde::{Deserialize, Deserializer};
 use std::fs::File;
 use std::path::Path;
 
-/// CSV record for OHLCV data with flexible field names
-#[derive(Debug, Deserialize)]
+/// CSV or JSON record for OHLCV data with flexible field names
+#[derive(Debug, Clone, Deserialize)]
 pub struct OHLCVRecord {
     #[serde(alias = "Date", alias = "Datetime", alias = "Time")]
     pub timestamp: String,
@@ -36,14 +36,32 @@ fn default_volume() -> f64 {
     1000000.0
 }
 
+/// Numeric field as it appears in the source: a JSON number, or text from CSV or JSON
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(f64),
+    String(String),
+}
+
+impl NumberOrString {
+    fn parse<E: serde::de::Error>(self) -> std::result::Result<f64, E> {
+        match self {
+            NumberOrString::Number(n) => Ok(n),
+            NumberOrString::String(s) => {
+                let cleaned: String = s.chars().filter(|&c| c != ',').collect();
+                cleaned.parse().map_err(E::custom)
+            }
+        }
+    }
+}
+
 /// Custom deserializer for numeric fields that may contain commas
 fn deserialize_number_with_commas<'de, D>(deserializer: D) -> std::result::Result<f64, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s = String::deserialize(deserializer)?;
-    let cleaned: String = s.chars().filter(|&c| c != ',').collect();
-    cleaned.parse().map_err(serde::de::Error::custom)
+    NumberOrString::deserialize(deserializer)?.parse()
 }
 
 /// Custom deserializer for optional numeric fields that may contain commas
@@ -51,13 +69,70 @@ fn deserialize_optional_number_with_commas<'de, D>(deserializer: D) -> std::resu
 where
     D: Deserializer<'de>,
 {
-    let s: Option<String> = Option::deserialize(deserializer)?;
-    match s {
-        Some(s) if !s.is_empty() => {
-            let cleaned: String = s.chars().filter(|&c| c != ',').collect();
-            cleaned.parse().map(Some).map_err(serde::de::Error::custom)
+    match Option::<NumberOrString>::deserialize(deserializer)? {
+        Some(NumberOrString::String(s)) if s.is_empty() => Ok(None),
+        Some(value) => value.parse().map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Bar size that `DataLoader::resample` aggregates into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timeframe {
+    /// Buckets of `n` minutes aligned to the Unix epoch
+    Minutes(u32),
+    Daily,
+    /// ISO weeks starting on Monday
+    Weekly,
+    Monthly,
+}
+
+impl Timeframe {
+    /// Start of the bucket `timestamp` falls into
+    pub fn bucket_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let midnight =
+            |date: chrono::NaiveDate| Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap());
+        match *self {
+            Timeframe::Minutes(n) => {
+                let bucket = i64::from(n.max(1)) * 60;
+                let seconds = timestamp.timestamp().div_euclid(bucket) * bucket;
+                Utc.timestamp_opt(seconds, 0).unwrap()
+            }
+            Timeframe::Daily => midnight(timestamp.date_naive()),
+            Timeframe::Weekly => {
+                let days_from_monday = timestamp.weekday().num_days_from_monday();
+                midnight(timestamp.date_naive() - Duration::days(i64::from(days_from_monday)))
+            }
+            Timeframe::Monthly => midnight(timestamp.date_naive().with_day(1).unwrap()),
         }
-        _ => Ok(None),
+    }
+}
+
+/// What to do with bars that share a timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Keep every bar
+    #[default]
+    Keep,
+    /// Fail the load
+    Error,
+    /// Keep only the first bar loaded for each timestamp
+    DropDuplicates,
+}
+
+/// Irregularities found by `DataLoader::validate_continuity`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContinuityReport {
+    /// Timestamps that appear more than once, listed once per extra bar
+    pub duplicates: Vec<DateTime<Utc>>,
+    /// `(previous, next)` timestamps of consecutive bars further apart than expected
+    pub gaps: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl ContinuityReport {
+    /// Whether no duplicates or gaps were found
+    pub fn is_clean(&self) -> bool {
+        self.duplicates.is_empty() && self.gaps.is_empty()
     }
 }
 
@@ -71,7 +146,7 @@ impl DataLoader {
     /// * `dataset_name` - Name of the CSV file (without .csv extension)
     /// 
     /// # Examples
-    /// ```
+    /// ```no_run
     /// use backtesting::data::DataLoader;
     /// 
     /// // Loads data/AAPL.csv
@@ -118,41 +193,240 @@ impl DataLoader {
                 .map_err(|e| format!("Failed to parse timestamp '{}' on line {}: {}", 
                     record.timestamp, line_num + 2, e))?;
             
-            // Validate OHLCV data
-            if record.open <= 0.0 || record.high <= 0.0 || record.low <= 0.0 || record.close <= 0.0 {
-                return Err(format!("Invalid price data on line {}: prices must be positive", line_num + 2).into());
-            }
+            let bar = OHLCV {
+                timestamp,
+                open: record.open,
+                high: record.high,
+                low: record.low,
+                close: record.close,
+                volume: record.volume,
+            };
+            Self::validate_bar(&bar)
+                .map_err(|e| format!("Invalid price data on line {}: {}", line_num + 2, e))?;
             
-            if record.high < record.low {
-                return Err(format!("Invalid price data on line {}: high ({}) < low ({})", 
-                    line_num + 2, record.high, record.low).into());
+            data.push(bar);
+        }
+        
+        if data.is_empty() {
+            return Err("No data found in CSV file".into());
+        }
+        
+        // Sort by timestamp to ensure chronological order
+        data.sort_by_key(|a| a.timestamp);
+        
+        Ok(data)
+    }
+    
+    /// Load OHLCV data from a CSV file, handling rows that share a timestamp according
+    /// to `duplicates`
+    pub fn load_from_file_with<P: AsRef<Path>>(
+        file_path: P,
+        duplicates: DuplicatePolicy,
+    ) -> Result<Vec<OHLCV>> {
+        let data = Self::load_from_file(file_path)?;
+        Self::apply_duplicate_policy(data, duplicates)
+    }
+    
+    /// Apply `policy` to chronologically sorted bars
+    pub fn apply_duplicate_policy(
+        mut data: Vec<OHLCV>,
+        policy: DuplicatePolicy,
+    ) -> Result<Vec<OHLCV>> {
+        match policy {
+            DuplicatePolicy::Keep => {}
+            DuplicatePolicy::Error => {
+                if let Some(pair) = data.windows(2).find(|w| w[0].timestamp == w[1].timestamp) {
+                    return Err(format!("Duplicate timestamp: {}", pair[0].timestamp).into());
+                }
             }
-            
-            if record.open > record.high || record.open < record.low ||
-               record.close > record.high || record.close < record.low {
-                return Err(format!("Invalid price data on line {}: open/close outside high/low range", line_num + 2).into());
+            DuplicatePolicy::DropDuplicates => data.dedup_by_key(|bar| bar.timestamp),
+        }
+        
+        Ok(data)
+    }
+    
+    /// Report duplicate timestamps and gaps wider than `expected_interval` between
+    /// consecutive bars of chronologically sorted data
+    /// 
+    /// Market closures show up as gaps too, e.g. weekends in daily stock data.
+    pub fn validate_continuity(data: &[OHLCV], expected_interval: Duration) -> ContinuityReport {
+        let mut report = ContinuityReport::default();
+        
+        for pair in data.windows(2) {
+            let (previous, current) = (pair[0].timestamp, pair[1].timestamp);
+            if current == previous {
+                report.duplicates.push(current);
+            } else if current - previous > expected_interval {
+                report.gaps.push((previous, current));
             }
+        }
+        
+        report
+    }
+    
+    /// Load OHLCV data from a JSON file holding an array of records
+    /// 
+    /// Records use the same field names and date formats as CSV rows; prices may be
+    /// JSON numbers or strings.
+    pub fn load_from_json<P: AsRef<Path>>(file_path: P) -> Result<Vec<OHLCV>> {
+        let file = File::open(file_path.as_ref())
+            .map_err(|e| format!("Failed to open file '{}': {}", file_path.as_ref().display(), e))?;
+        let records: Vec<OHLCVRecord> = serde_json::from_reader(std::io::BufReader::new(file))
+            .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        Self::from_records(records)
+    }
+    
+    /// Build validated, chronologically sorted OHLCV data from records already in memory,
+    /// e.g. fetched from an API
+    pub fn from_records(records: Vec<OHLCVRecord>) -> Result<Vec<OHLCV>> {
+        let mut data = Vec::with_capacity(records.len());
+        
+        for (index, record) in records.into_iter().enumerate() {
+            let timestamp = Self::parse_timestamp(&record.timestamp)
+                .map_err(|e| format!("Failed to parse timestamp '{}' in record {}: {}", 
+                    record.timestamp, index, e))?;
             
-            data.push(OHLCV {
+            let bar = OHLCV {
                 timestamp,
                 open: record.open,
                 high: record.high,
                 low: record.low,
                 close: record.close,
                 volume: record.volume,
-            });
+            };
+            Self::validate_bar(&bar)
+                .map_err(|e| format!("Invalid price data in record {}: {}", index, e))?;
+            
+            data.push(bar);
         }
         
         if data.is_empty() {
-            return Err("No data found in CSV file".into());
+            return Err("No data found in records".into());
         }
         
         // Sort by timestamp to ensure chronological order
-        data.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        data.sort_by_key(|a| a.timestamp);
         
         Ok(data)
     }
     
+    /// Load OHLCV data from a Parquet file
+    /// 
+    /// Columns are matched by the same names as the CSV loader (`timestamp`/`Date`/...,
+    /// `open`/`Open`, ...). The timestamp column may be a date, a datetime or a string in
+    /// any of the CSV date formats, and a missing volume column uses the CSV default.
+    #[cfg(feature = "parquet")]
+    pub fn load_from_parquet<P: AsRef<Path>>(file_path: P) -> Result<Vec<OHLCV>> {
+        use polars::prelude::*;
+        
+        let file = File::open(file_path.as_ref())
+            .map_err(|e| format!("Failed to open file '{}': {}", file_path.as_ref().display(), e))?;
+        let df = ParquetReader::new(file).finish()?;
+        
+        let find_column = |names: &[&str]| names.iter().find_map(|name| df.column(name).ok());
+        let price_column = |names: &[&str]| -> Result<Vec<Option<f64>>> {
+            let column = find_column(names)
+                .ok_or_else(|| format!("Missing '{}' column in Parquet file", names[0]))?;
+            Ok(column.cast(&DataType::Float64)?.f64()?.into_iter().collect())
+        };
+        
+        let timestamp_column = find_column(&["timestamp", "Date", "Datetime", "Time"])
+            .ok_or("Missing 'timestamp' column in Parquet file")?;
+        let timestamps: Vec<Option<DateTime<Utc>>> = match timestamp_column.dtype() {
+            DataType::Utf8 => timestamp_column
+                .utf8()?
+                .into_iter()
+                .map(|value| value.map(Self::parse_timestamp).transpose())
+                .collect::<Result<_>>()?,
+            _ => timestamp_column
+                .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))?
+                .cast(&DataType::Int64)?
+                .i64()?
+                .into_iter()
+                .map(|millis| millis.and_then(DateTime::from_timestamp_millis))
+                .collect(),
+        };
+        
+        let open = price_column(&["open", "Open"])?;
+        let high = price_column(&["high", "High"])?;
+        let low = price_column(&["low", "Low"])?;
+        let close = price_column(&["close", "Close"])?;
+        let volume = match find_column(&["volume", "Volume"]) {
+            Some(_) => price_column(&["volume", "Volume"])?,
+            None => vec![Some(default_volume()); df.height()],
+        };
+        
+        let mut data = Vec::with_capacity(df.height());
+        for row in 0..df.height() {
+            let missing = || format!("Missing value on row {}", row + 1);
+            let bar = OHLCV {
+                timestamp: timestamps[row].ok_or_else(missing)?,
+                open: open[row].ok_or_else(missing)?,
+                high: high[row].ok_or_else(missing)?,
+                low: low[row].ok_or_else(missing)?,
+                close: close[row].ok_or_else(missing)?,
+                volume: volume[row].unwrap_or_else(default_volume),
+            };
+            Self::validate_bar(&bar)
+                .map_err(|e| format!("Invalid price data on row {}: {}", row + 1, e))?;
+            data.push(bar);
+        }
+        
+        if data.is_empty() {
+            return Err("No data found in Parquet file".into());
+        }
+        
+        // Sort by timestamp to ensure chronological order
+        data.sort_by_key(|a| a.timestamp);
+        
+        Ok(data)
+    }
+    
+    /// Aggregate chronologically sorted bars into `timeframe` bars
+    /// 
+    /// Each output bar takes the first open, highest high, lowest low, last close and
+    /// total volume of its bucket and is stamped with the bucket start. The last bucket
+    /// may be partial, e.g. a month that is still in progress.
+    pub fn resample(data: &[OHLCV], timeframe: Timeframe) -> Vec<OHLCV> {
+        let mut resampled: Vec<OHLCV> = Vec::new();
+        
+        for bar in data {
+            let bucket = timeframe.bucket_start(bar.timestamp);
+            match resampled.last_mut() {
+                Some(current) if current.timestamp == bucket => {
+                    current.high = current.high.max(bar.high);
+                    current.low = current.low.min(bar.low);
+                    current.close = bar.close;
+                    current.volume += bar.volume;
+                }
+                _ => resampled.push(OHLCV {
+                    timestamp: bucket,
+                    ..bar.clone()
+                }),
+            }
+        }
+        
+        resampled
+    }
+    
+    /// Check that prices are positive and open/close lie within the high/low range
+    fn validate_bar(bar: &OHLCV) -> std::result::Result<(), String> {
+        if bar.open <= 0.0 || bar.high <= 0.0 || bar.low <= 0.0 || bar.close <= 0.0 {
+            return Err("prices must be positive".to_string());
+        }
+        
+        if bar.high < bar.low {
+            return Err(format!("high ({}) < low ({})", bar.high, bar.low));
+        }
+        
+        if bar.open > bar.high || bar.open < bar.low ||
+           bar.close > bar.high || bar.close < bar.low {
+            return Err("open/close outside high/low range".to_string());
+        }
+        
+        Ok(())
+    }
+    
     /// Parse various timestamp formats
     fn parse_timestamp(timestamp_str: &str) -> Result<DateTime<Utc>> {
         // Try different timestamp formats
@@ -217,30 +491,82 @@ impl DataLoader {
         let mut file = File::create("data/sample.csv")?;
         writeln!(file, "Date,Open,High,Low,Close,Volume")?;
         
-        let start_date = chrono::Utc::now().date_naive() - chrono::Duration::days(365);
-        let mut price = 100.0;
+        let start_date = chrono::Utc::now() - chrono::Duration::days(365);
+        let data = Self::generate_sample_data(&mut rand::thread_rng(), start_date, 252, 100.0);
         
-        for i in 0..252 { // One year of trading days
-            let date = start_date + chrono::Duration::days(i);
-            
-            // Simulate price movement
-            let change = (i as f64 * 0.1).sin() * 2.0 + (rand::random::<f64>() - 0.5) * 3.0;
-            price += change;
-            price = price.max(50.0); // Floor price
+        for bar in data {
+            writeln!(file, "{},{:.2},{:.2},{:.2},{:.2},{:.0}", 
+                bar.timestamp.format("%Y-%m-%d"), bar.open, bar.high, bar.low, bar.close,
+                bar.volume)?;
+        }
+        
+        println!("Sample data created at data/sample.csv");
+        Ok(())
+    }
+    
+    /// Generate `days` daily bars of sample data starting on 2023-01-01 at `start_price`.
+    ///
+    /// The same seed always produces the same bars, so the data can back reproducible
+    /// examples and tests.
+    pub fn create_sample_data_seeded(seed: u64, days: usize, start_price: f64) -> Vec<OHLCV> {
+        use rand::SeedableRng;
+        
+        let start_date = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        Self::generate_sample_data(&mut rng, start_date, days, start_price)
+    }
+    
+    /// Random walk with a slow sine drift, one bar per calendar day
+    fn generate_sample_data<R: rand::Rng>(
+        rng: &mut R,
+        start_date: DateTime<Utc>,
+        days: usize,
+        start_price: f64,
+    ) -> Vec<OHLCV> {
+        let mut price = start_price;
+        let floor = start_price * 0.5;
+        let mut data = Vec::with_capacity(days);
+        
+        for i in 0..days {
+            // Moves are percentages of the current price, so bars stay positive at any scale
+            let pct = price / 100.0;
+            let change = (i as f64 * 0.1).sin() * 2.0 + (rng.gen::<f64>() - 0.5) * 3.0;
+            price += change * pct;
+            price = price.max(floor); // Floor price
             
             let open = price;
-            let high = price + (rand::random::<f64>() * 3.0);
-            let low = price - (rand::random::<f64>() * 3.0);
-            let close = low + (rand::random::<f64>() * (high - low));
-            let volume = 1000000.0 + (rand::random::<f64>() * 500000.0);
+            let high = price + (rng.gen::<f64>() * 3.0 * pct);
+            let low = price - (rng.gen::<f64>() * 3.0 * pct);
+            let close = low + (rng.gen::<f64>() * (high - low));
+            let volume = 1000000.0 + (rng.gen::<f64>() * 500000.0);
             
             price = close; // Next day starts at previous close
             
-            writeln!(file, "{},{:.2},{:.2},{:.2},{:.2},{:.0}", 
-                date.format("%Y-%m-%d"), open, high, low, close, volume)?;
+            data.push(OHLCV {
+                timestamp: start_date + Duration::days(i as i64),
+                open,
+                high,
+                low,
+                close,
+                volume,
+            });
         }
         
-        println!("Sample data created at data/sample.csv");
-        Ok(())
+        data
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_data_stays_valid_at_small_start_prices() {
+        for start_price in [0.5, 2.0, 100.0] {
+            let data = DataLoader::create_sample_data_seeded(3, 500, start_price);
+            for bar in &data {
+                assert_eq!(DataLoader::validate_bar(bar), Ok(()), "{bar:?} from {start_price}");
+            }
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use thiserror::Error;
+
+/// Errors raised by the backtesting engine
+#[derive(Debug, Error)]
+pub enum BacktestError {
+    #[error("insufficient data: strategy needs {need} bars but only {have} are available")]
+    InsufficientData { have: usize, need: usize },
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+    #[error("no price for {0}")]
+    MissingPrice(String),
+}
@@ -0,0 +1,141 @@
+use crate::types::OHLCV;
+use super::Indicator;
+
+#[derive(Debug, Clone)]
+pub struct AdxOutput {
+    pub adx: f64,
+    pub plus_di: f64,
+    pub minus_di: f64,
+}
+
+/// Average Directional Index with Wilder smoothing.
+///
+/// True range and the +DM/-DM directional movements are Wilder-smoothed over `period`
+/// bars to give +DI and -DI; ADX is the Wilder average of DX, the normalized gap
+/// between them. The first bar only seeds the previous high, low and close, so the DIs
+/// start after `period + 1` bars and ADX after `2 * period`.
+pub struct ADX {
+    prev_bar: Option<(f64, f64, f64)>,
+    true_range: WilderAverage,
+    plus_dm: WilderAverage,
+    minus_dm: WilderAverage,
+    adx: WilderAverage,
+    current_value: Option<AdxOutput>,
+}
+
+impl ADX {
+    pub fn new(period: usize) -> Self {
+        Self {
+            prev_bar: None,
+            true_range: WilderAverage::new(period),
+            plus_dm: WilderAverage::new(period),
+            minus_dm: WilderAverage::new(period),
+            adx: WilderAverage::new(period),
+            current_value: None,
+        }
+    }
+}
+
+impl Indicator for ADX {
+    type Output = AdxOutput;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<AdxOutput> {
+        let (prev_high, prev_low, prev_close) =
+            self.prev_bar.replace((bar.high, bar.low, bar.close))?;
+        
+        let true_range = (bar.high - bar.low)
+            .max((bar.high - prev_close).abs())
+            .max((bar.low - prev_close).abs());
+        let up_move = bar.high - prev_high;
+        let down_move = prev_low - bar.low;
+        let plus_dm = if up_move > down_move && up_move > 0.0 {
+            up_move
+        } else {
+            0.0
+        };
+        let minus_dm = if down_move > up_move && down_move > 0.0 {
+            down_move
+        } else {
+            0.0
+        };
+        
+        let true_range = self.true_range.update(true_range);
+        let plus_dm = self.plus_dm.update(plus_dm);
+        let minus_dm = self.minus_dm.update(minus_dm);
+        let (Some(true_range), Some(plus_dm), Some(minus_dm)) = (true_range, plus_dm, minus_dm)
+        else {
+            return None;
+        };
+        
+        let (plus_di, minus_di) = if true_range > 0.0 {
+            (100.0 * plus_dm / true_range, 100.0 * minus_dm / true_range)
+        } else {
+            (0.0, 0.0)
+        };
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0.0 {
+            100.0 * (plus_di - minus_di).abs() / di_sum
+        } else {
+            0.0
+        };
+        
+        let adx = self.adx.update(dx)?;
+        self.current_value = Some(AdxOutput {
+            adx,
+            plus_di,
+            minus_di,
+        });
+        self.current()
+    }
+    
+    fn current(&self) -> Option<AdxOutput> {
+        self.current_value.clone()
+    }
+    
+    fn reset(&mut self) {
+        self.prev_bar = None;
+        self.true_range.reset();
+        self.plus_dm.reset();
+        self.minus_dm.reset();
+        self.adx.reset();
+        self.current_value = None;
+    }
+}
+
+/// Wilder's running average: the mean of the first `period` values, then each value
+/// moves it by `(value - average) / period`
+struct WilderAverage {
+    period: usize,
+    count: usize,
+    sum: f64,
+    value: Option<f64>,
+}
+
+impl WilderAverage {
+    fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            count: 0,
+            sum: 0.0,
+            value: None,
+        }
+    }
+    
+    fn update(&mut self, x: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(average) => Some(average + (x - average) / self.period as f64),
+            None => {
+                self.count += 1;
+                self.sum += x;
+                (self.count >= self.period).then(|| self.sum / self.period as f64)
+            }
+        };
+        self.value
+    }
+    
+    fn reset(&mut self) {
+        self.count = 0;
+        self.sum = 0.0;
+        self.value = None;
+    }
+}
\ No newline at end of file
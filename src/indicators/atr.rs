@@ -0,0 +1,62 @@
+use crate::types::OHLCV;
+use super::Indicator;
+
+/// Average True Range with Wilder smoothing.
+///
+/// The first value is the mean of the first `period` true ranges; after that each
+/// bar moves the average by `(true_range - atr) / period`.
+pub struct ATR {
+    period: usize,
+    prev_close: Option<f64>,
+    count: usize,
+    sum: f64,
+    current_value: Option<f64>,
+}
+
+impl ATR {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            prev_close: None,
+            count: 0,
+            sum: 0.0,
+            current_value: None,
+        }
+    }
+}
+
+impl Indicator for ATR {
+    type Output = f64;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<f64> {
+        let true_range = match self.prev_close {
+            Some(prev_close) => (bar.high - bar.low)
+                .max((bar.high - prev_close).abs())
+                .max((bar.low - prev_close).abs()),
+            None => bar.high - bar.low,
+        };
+        self.prev_close = Some(bar.close);
+        
+        self.current_value = match self.current_value {
+            Some(atr) => Some(atr + (true_range - atr) / self.period as f64),
+            None => {
+                self.count += 1;
+                self.sum += true_range;
+                (self.count >= self.period).then(|| self.sum / self.period as f64)
+            }
+        };
+        
+        self.current()
+    }
+    
+    fn current(&self) -> Option<f64> {
+        self.current_value
+    }
+    
+    fn reset(&mut self) {
+        self.prev_close = None;
+        self.count = 0;
+        self.sum = 0.0;
+        self.current_value = None;
+    }
+}
\ No newline at end of file
@@ -1,5 +1,5 @@
 use crate::types::OHLCV;
-use super::Indicator;
+use super::{Indicator, PriceSource, RollingMoments};
 
 #[derive(Debug, Clone)]
 pub struct BollingerBandsOutput {
@@ -8,31 +8,36 @@ pub struct BollingerBandsOutput {
     pub lower: f64,
 }
 
+/// Bands `std_dev` population standard deviations either side of a simple moving
+/// average.
+///
+/// The window's mean and variance are kept incrementally, so each update is amortised
+/// O(1) and stays accurate over long series of high prices.
 pub struct BollingerBands {
-    period: usize,
     std_dev: f64,
-    values: Vec<f64>,
+    values: RollingMoments,
+    source: PriceSource,
 }
 
 impl BollingerBands {
     pub fn new(period: usize, std_dev: f64) -> Self {
+        Self::with_source(period, std_dev, PriceSource::Close)
+    }
+
+    pub fn with_source(period: usize, std_dev: f64, source: PriceSource) -> Self {
         Self {
-            period,
             std_dev,
-            values: Vec::with_capacity(period),
+            values: RollingMoments::new(period),
+            source,
         }
     }
     
-    fn calculate_std_dev(&self, mean: f64) -> f64 {
+    fn calculate_std_dev(&self) -> f64 {
         if self.values.len() < 2 {
             return 0.0;
         }
         
-        let variance = self.values.iter()
-            .map(|&x| (x - mean).powi(2))
-            .sum::<f64>() / self.values.len() as f64;
-        
-        variance.sqrt()
+        self.values.variance(false).unwrap_or(0.0).sqrt()
     }
 }
 
@@ -40,30 +45,14 @@ impl Indicator for BollingerBands {
     type Output = BollingerBandsOutput;
     
     fn update(&mut self, bar: &OHLCV) -> Option<BollingerBandsOutput> {
-        self.values.push(bar.close);
-        
-        if self.values.len() > self.period {
-            self.values.remove(0);
-        }
-        
-        if self.values.len() == self.period {
-            let mean = self.values.iter().sum::<f64>() / self.period as f64;
-            let std_deviation = self.calculate_std_dev(mean);
-            
-            Some(BollingerBandsOutput {
-                upper: mean + (self.std_dev * std_deviation),
-                middle: mean,
-                lower: mean - (self.std_dev * std_deviation),
-            })
-        } else {
-            None
-        }
+        self.values.push(self.source.value(bar));
+        self.current()
     }
     
     fn current(&self) -> Option<BollingerBandsOutput> {
-        if self.values.len() == self.period {
-            let mean = self.values.iter().sum::<f64>() / self.period as f64;
-            let std_deviation = self.calculate_std_dev(mean);
+        if self.values.is_full() {
+            let mean = self.values.mean()?;
+            let std_deviation = self.calculate_std_dev();
             
             Some(BollingerBandsOutput {
                 upper: mean + (self.std_dev * std_deviation),
@@ -74,4 +63,49 @@ impl Indicator for BollingerBands {
             None
         }
     }
+    
+    fn reset(&mut self) {
+        self.values.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::flat_bars;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Mean and population standard deviation of `window`, summed from scratch
+    fn naive(window: &[f64]) -> (f64, f64) {
+        let n = window.len() as f64;
+        let mean = window.iter().sum::<f64>() / n;
+        let variance = window.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        (mean, variance.sqrt())
+    }
+
+    #[test]
+    fn streaming_bands_match_a_naive_recomputation() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let closes: Vec<f64> = (0..20_000)
+            .map(|_| 50_000.0 + rng.gen_range(-2.0..2.0))
+            .collect();
+        let period = 20;
+        let mut bands = BollingerBands::new(period, 2.0);
+
+        for (i, bar) in flat_bars(&closes).iter().enumerate() {
+            let output = bands.update(bar);
+            if i + 1 < period {
+                assert!(output.is_none());
+                continue;
+            }
+
+            let output = output.unwrap();
+            let (mean, std_dev) = naive(&closes[i + 1 - period..=i]);
+            assert!((output.middle - mean).abs() < 1e-9, "bar {i}");
+            let (upper, lower) = (mean + 2.0 * std_dev, mean - 2.0 * std_dev);
+            assert!((output.upper - upper).abs() < 1e-8, "bar {i}");
+            assert!((output.lower - lower).abs() < 1e-8, "bar {i}");
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,57 @@
+use crate::types::OHLCV;
+use super::{Indicator, PriceSource, RollingWindow};
+
+/// Commodity Channel Index.
+///
+/// How far the typical price `(high + low + close) / 3` sits from its `period`-bar SMA,
+/// in units of 0.015 times the mean absolute deviation of the typical price from that
+/// SMA. A flat window (zero deviation) gives 0.
+pub struct CCI {
+    typical: RollingWindow,
+    current_value: Option<f64>,
+}
+
+impl CCI {
+    pub fn new(period: usize) -> Self {
+        Self {
+            typical: RollingWindow::new(period),
+            current_value: None,
+        }
+    }
+}
+
+impl Indicator for CCI {
+    type Output = f64;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<f64> {
+        let typical = PriceSource::Typical.value(bar);
+        self.typical.push(typical);
+        if !self.typical.is_full() {
+            return None;
+        }
+        
+        let mean = self.typical.mean()?;
+        let mean_deviation = self
+            .typical
+            .iter()
+            .map(|value| (value - mean).abs())
+            .sum::<f64>()
+            / self.typical.len() as f64;
+        
+        self.current_value = Some(if mean_deviation > 0.0 {
+            (typical - mean) / (0.015 * mean_deviation)
+        } else {
+            0.0
+        });
+        self.current()
+    }
+    
+    fn current(&self) -> Option<f64> {
+        self.current_value
+    }
+    
+    fn reset(&mut self) {
+        self.typical.clear();
+        self.current_value = None;
+    }
+}
\ No newline at end of file
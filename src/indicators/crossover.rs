@@ -0,0 +1,53 @@
+/// Detects the bar on which one series crosses another.
+///
+/// Feed both series' values each bar through `update`; `crossed_above` and
+/// `crossed_below` are true only on the bar where the relationship flips. Bars where
+/// the series are equal or either value is NaN leave the last strict relationship in
+/// place, so touching and bouncing back doesn't count as a cross.
+#[derive(Debug, Clone, Default)]
+pub struct Crossover {
+    a_above: Option<bool>,
+    crossed_above: bool,
+    crossed_below: bool,
+}
+
+impl Crossover {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Record this bar's values of series `a` and `b`
+    pub fn update(&mut self, a: f64, b: f64) {
+        self.crossed_above = false;
+        self.crossed_below = false;
+        
+        let a_above = if a > b {
+            true
+        } else if a < b {
+            false
+        } else {
+            return;
+        };
+        
+        match self.a_above.replace(a_above) {
+            Some(false) if a_above => self.crossed_above = true,
+            Some(true) if !a_above => self.crossed_below = true,
+            _ => {}
+        }
+    }
+    
+    /// Whether `a` moved above `b` on the last update
+    pub fn crossed_above(&self) -> bool {
+        self.crossed_above
+    }
+    
+    /// Whether `a` moved below `b` on the last update
+    pub fn crossed_below(&self) -> bool {
+        self.crossed_below
+    }
+    
+    /// Forget the last relationship so the next update can't register a cross
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,60 @@
+use crate::types::OHLCV;
+use super::{Indicator, RollingWindow};
+
+#[derive(Debug, Clone)]
+pub struct DonchianOutput {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+/// Donchian channel.
+///
+/// The upper band is the highest high and the lower band the lowest low of the last
+/// `period` bars; the middle band is halfway between them.
+pub struct DonchianChannel {
+    highs: RollingWindow,
+    lows: RollingWindow,
+    current_value: Option<DonchianOutput>,
+}
+
+impl DonchianChannel {
+    pub fn new(period: usize) -> Self {
+        Self {
+            highs: RollingWindow::new(period),
+            lows: RollingWindow::new(period),
+            current_value: None,
+        }
+    }
+}
+
+impl Indicator for DonchianChannel {
+    type Output = DonchianOutput;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<DonchianOutput> {
+        self.highs.push(bar.high);
+        self.lows.push(bar.low);
+        if !self.highs.is_full() {
+            return None;
+        }
+        
+        let upper = self.highs.max()?;
+        let lower = self.lows.min()?;
+        self.current_value = Some(DonchianOutput {
+            upper,
+            middle: (upper + lower) / 2.0,
+            lower,
+        });
+        self.current()
+    }
+    
+    fn current(&self) -> Option<DonchianOutput> {
+        self.current_value.clone()
+    }
+    
+    fn reset(&mut self) {
+        self.highs.clear();
+        self.lows.clear();
+        self.current_value = None;
+    }
+}
\ No newline at end of file
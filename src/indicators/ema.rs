@@ -1,17 +1,23 @@
 use crate::types::OHLCV;
-use super::Indicator;
+use super::{Indicator, PriceSource};
 
 pub struct ExponentialMovingAverage {
     alpha: f64,
     current_value: Option<f64>,
+    source: PriceSource,
 }
 
 impl ExponentialMovingAverage {
     pub fn new(window: usize) -> Self {
+        Self::with_source(window, PriceSource::Close)
+    }
+
+    pub fn with_source(window: usize, source: PriceSource) -> Self {
         let alpha = 2.0 / (window as f64 + 1.0);
         Self {
             alpha,
             current_value: None,
+            source,
         }
     }
 }
@@ -20,13 +26,14 @@ impl Indicator for ExponentialMovingAverage {
     type Output = f64;
     
     fn update(&mut self, bar: &OHLCV) -> Option<f64> {
+        let price = self.source.value(bar);
         match self.current_value {
             None => {
-                self.current_value = Some(bar.close);
-                Some(bar.close)
+                self.current_value = Some(price);
+                Some(price)
             }
             Some(prev) => {
-                let new_value = self.alpha * bar.close + (1.0 - self.alpha) * prev;
+                let new_value = self.alpha * price + (1.0 - self.alpha) * prev;
                 self.current_value = Some(new_value);
                 Some(new_value)
             }
@@ -36,4 +43,8 @@ impl Indicator for ExponentialMovingAverage {
     fn current(&self) -> Option<f64> {
         self.current_value
     }
+    
+    fn reset(&mut self) {
+        self.current_value = None;
+    }
 }
\ No newline at end of file
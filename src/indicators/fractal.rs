@@ -0,0 +1,101 @@
+use crate::types::OHLCV;
+use super::Indicator;
+use std::collections::VecDeque;
+
+const FRACTAL_WINDOW: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct FractalOutput {
+    /// High of the confirmed swing-high bar, if any
+    pub up: Option<f64>,
+    /// Low of the confirmed swing-low bar, if any
+    pub down: Option<f64>,
+}
+
+/// Bill Williams fractal detector.
+///
+/// A fractal needs two bars on each side to be confirmed, so the value returned
+/// by `update` describes the bar two bars *before* the one just passed in.
+/// This delay avoids look-ahead: a swing point is only reported once it is known.
+pub struct Fractal {
+    bars: VecDeque<(f64, f64)>,
+    current_value: Option<FractalOutput>,
+}
+
+impl Fractal {
+    pub fn new() -> Self {
+        Self {
+            bars: VecDeque::with_capacity(FRACTAL_WINDOW),
+            current_value: None,
+        }
+    }
+}
+
+impl Default for Fractal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Indicator for Fractal {
+    type Output = FractalOutput;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<FractalOutput> {
+        self.bars.push_back((bar.high, bar.low));
+        if self.bars.len() > FRACTAL_WINDOW {
+            self.bars.pop_front();
+        }
+        
+        if self.bars.len() < FRACTAL_WINDOW {
+            return None;
+        }
+        
+        let middle = FRACTAL_WINDOW / 2;
+        let (mid_high, mid_low) = self.bars[middle];
+        let mut others = self.bars.iter().enumerate().filter(|(i, _)| *i != middle);
+        
+        let is_up = others.clone().all(|(_, (high, _))| mid_high > *high);
+        let is_down = others.all(|(_, (_, low))| mid_low < *low);
+        
+        self.current_value = Some(FractalOutput {
+            up: is_up.then_some(mid_high),
+            down: is_down.then_some(mid_low),
+        });
+        self.current_value.clone()
+    }
+    
+    fn current(&self) -> Option<FractalOutput> {
+        self.current_value.clone()
+    }
+    
+    fn reset(&mut self) {
+        self.bars.clear();
+        self.current_value = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::bars;
+
+    #[test]
+    fn a_peak_is_reported_two_bars_after_it() {
+        // A clear peak at bar 3, with the lows rising into it and falling away
+        let highs = [10.0, 11.0, 12.0, 15.0, 12.0, 11.0, 10.0];
+        let prices: Vec<_> = highs
+            .iter()
+            .map(|&h| (h - 1.0, h, h - 2.0, h - 1.0))
+            .collect();
+
+        let mut fractal = Fractal::new();
+        let ups: Vec<_> = bars(&prices)
+            .iter()
+            .map(|bar| fractal.update(bar).map(|output| output.up))
+            .collect();
+
+        // Nothing until five bars, and the peak only once the two bars after it close
+        assert_eq!(ups[..4], [None; 4]);
+        assert_eq!(ups[4..], [Some(None), Some(Some(15.0)), Some(None)]);
+    }
+}
\ No newline at end of file
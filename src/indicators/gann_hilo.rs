@@ -0,0 +1,121 @@
+use crate::types::OHLCV;
+use super::{Indicator, RollingWindow};
+
+#[derive(Debug, Clone)]
+pub struct GannHiLoOutput {
+    pub value: f64,
+    pub is_uptrend: bool,
+}
+
+/// Gann HiLo Activator: trails the SMA of lows while price is above the SMA of
+/// highs, and the SMA of highs while price is below the SMA of lows.
+///
+/// The close is compared against the previous bar's averages, so the direction
+/// flips on the bar that crosses the opposite line.
+pub struct GannHiLoActivator {
+    highs: RollingWindow,
+    lows: RollingWindow,
+    prev_smas: Option<(f64, f64)>,
+    is_uptrend: Option<bool>,
+    current_value: Option<GannHiLoOutput>,
+}
+
+impl GannHiLoActivator {
+    pub fn new(period: usize) -> Self {
+        Self {
+            highs: RollingWindow::new(period),
+            lows: RollingWindow::new(period),
+            prev_smas: None,
+            is_uptrend: None,
+            current_value: None,
+        }
+    }
+}
+
+impl Indicator for GannHiLoActivator {
+    type Output = GannHiLoOutput;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<GannHiLoOutput> {
+        self.highs.push(bar.high);
+        self.lows.push(bar.low);
+        
+        if !self.highs.is_full() {
+            return None;
+        }
+        
+        let high_sma = self.highs.mean()?;
+        let low_sma = self.lows.mean()?;
+        
+        // Flip only when the close crosses the opposite line
+        let (prev_high_sma, prev_low_sma) = self.prev_smas.unwrap_or((high_sma, low_sma));
+        let is_uptrend = if bar.close > prev_high_sma {
+            true
+        } else if bar.close < prev_low_sma {
+            false
+        } else {
+            self.is_uptrend
+                .unwrap_or(bar.close >= (high_sma + low_sma) / 2.0)
+        };
+        
+        self.prev_smas = Some((high_sma, low_sma));
+        self.is_uptrend = Some(is_uptrend);
+        self.current_value = Some(GannHiLoOutput {
+            value: if is_uptrend { low_sma } else { high_sma },
+            is_uptrend,
+        });
+        self.current_value.clone()
+    }
+    
+    fn current(&self) -> Option<GannHiLoOutput> {
+        self.current_value.clone()
+    }
+    
+    fn reset(&mut self) {
+        self.highs.clear();
+        self.lows.clear();
+        self.prev_smas = None;
+        self.is_uptrend = None;
+        self.current_value = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::bars;
+
+    #[test]
+    fn flips_once_at_a_clean_reversal() {
+        // Closes climb by 2 from 100 to 120, then fall by 2 to 90, each bar a point
+        // either side of its close
+        let closes = (0..=10).map(|i| 100.0 + 2.0 * i as f64);
+        let closes: Vec<f64> = closes
+            .chain((1..=15).map(|i| 120.0 - 2.0 * i as f64))
+            .collect();
+        let prices: Vec<_> = closes.iter().map(|&c| (c, c + 1.0, c - 1.0, c)).collect();
+
+        let mut gann = GannHiLoActivator::new(3);
+        let outputs: Vec<_> = bars(&prices).iter().map(|bar| gann.update(bar)).collect();
+        assert!(outputs[..2].iter().all(Option::is_none));
+
+        let trend: Vec<bool> = outputs[2..]
+            .iter()
+            .map(|o| o.as_ref().unwrap().is_uptrend)
+            .collect();
+        let flips: Vec<usize> = trend
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| pair[0] != pair[1])
+            .map(|(i, _)| i + 3)
+            .collect();
+        // 118 holds above the 117 low average, 116 closes under the next one's 117.67
+        assert_eq!(flips, [12]);
+        assert!(trend[0]);
+
+        // Trailing the low average before the flip and the high average after it
+        let average = |i: usize, offset: f64| closes[i - 2..=i].iter().sum::<f64>() / 3.0 + offset;
+        let value = |i: usize| outputs[i].as_ref().unwrap().value;
+        assert!((value(11) - average(11, -1.0)).abs() < 1e-9);
+        assert!((value(12) - average(12, 1.0)).abs() < 1e-9);
+    }
+}
\ No newline at end of file
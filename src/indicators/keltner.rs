@@ -0,0 +1,72 @@
+use crate::types::OHLCV;
+use super::{ExponentialMovingAverage, Indicator, ATR};
+
+#[derive(Debug, Clone)]
+pub struct KeltnerOutput {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+/// Keltner channel.
+///
+/// The middle line is an EMA of the close and the bands sit `multiplier` ATRs above
+/// and below it. Output starts once the EMA has seen `ema_period` bars and the ATR
+/// has seen `atr_period` bars.
+pub struct KeltnerChannel {
+    ema: ExponentialMovingAverage,
+    atr: ATR,
+    ema_period: usize,
+    multiplier: f64,
+    bars: usize,
+    current_value: Option<KeltnerOutput>,
+}
+
+impl KeltnerChannel {
+    pub fn new(ema_period: usize, atr_period: usize, multiplier: f64) -> Self {
+        Self {
+            ema: ExponentialMovingAverage::new(ema_period),
+            atr: ATR::new(atr_period),
+            ema_period,
+            multiplier,
+            bars: 0,
+            current_value: None,
+        }
+    }
+}
+
+impl Indicator for KeltnerChannel {
+    type Output = KeltnerOutput;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<KeltnerOutput> {
+        self.bars += 1;
+        let ema = self.ema.update(bar);
+        let atr = self.atr.update(bar);
+        
+        let (Some(middle), Some(atr)) = (ema, atr) else {
+            return None;
+        };
+        if self.bars < self.ema_period {
+            return None;
+        }
+        
+        let width = self.multiplier * atr;
+        self.current_value = Some(KeltnerOutput {
+            upper: middle + width,
+            middle,
+            lower: middle - width,
+        });
+        self.current()
+    }
+    
+    fn current(&self) -> Option<KeltnerOutput> {
+        self.current_value.clone()
+    }
+    
+    fn reset(&mut self) {
+        self.ema.reset();
+        self.atr.reset();
+        self.bars = 0;
+        self.current_value = None;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,85 @@
+use crate::types::OHLCV;
+use super::{ExponentialMovingAverage, Indicator};
+
+#[derive(Debug, Clone)]
+pub struct MacdOutput {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+/// Moving Average Convergence Divergence.
+///
+/// The MACD line is the fast EMA minus the slow EMA of the close; the signal line is
+/// an EMA of the MACD line. Output starts once the slow EMA has seen `slow` bars and
+/// the signal EMA has seen `signal` MACD values.
+pub struct MACD {
+    fast: ExponentialMovingAverage,
+    slow: ExponentialMovingAverage,
+    signal: ExponentialMovingAverage,
+    slow_period: usize,
+    signal_period: usize,
+    bars: usize,
+    signal_values: usize,
+    current_value: Option<MacdOutput>,
+}
+
+impl MACD {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self {
+            fast: ExponentialMovingAverage::new(fast),
+            slow: ExponentialMovingAverage::new(slow),
+            signal: ExponentialMovingAverage::new(signal),
+            slow_period: slow,
+            signal_period: signal,
+            bars: 0,
+            signal_values: 0,
+            current_value: None,
+        }
+    }
+}
+
+impl Indicator for MACD {
+    type Output = MacdOutput;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<MacdOutput> {
+        self.bars += 1;
+        let fast = self.fast.update(bar)?;
+        let slow = self.slow.update(bar)?;
+        
+        if self.bars < self.slow_period {
+            return None;
+        }
+        
+        let macd = fast - slow;
+        let signal = self.signal.update(&OHLCV {
+            close: macd,
+            ..bar.clone()
+        })?;
+        self.signal_values += 1;
+        
+        if self.signal_values < self.signal_period {
+            return None;
+        }
+        
+        self.current_value = Some(MacdOutput {
+            macd,
+            signal,
+            histogram: macd - signal,
+        });
+        self.current()
+    }
+    
+    fn current(&self) -> Option<MacdOutput> {
+        self.current_value.clone()
+    }
+    
+    fn reset(&mut self) {
+        self.fast.reset();
+        self.slow.reset();
+        self.signal.reset();
+        self.bars = 0;
+        self.signal_values = 0;
+        self.current_value = None;
+    }
+}
\ No newline at end of file
@@ -5,16 +5,60 @@ pub trait Indicator {
     
     fn update(&mut self, bar: &OHLCV) -> Option<Self::Output>;
     fn current(&self) -> Option<Self::Output>;
+    
+    /// Return the indicator to its just-constructed state, keeping its parameters, so
+    /// one instance can be reused on another series
+    fn reset(&mut self) {}
 }
 
+mod rolling_window;
+mod price_source;
 mod sma;
 mod ema;
 mod rsi;
 mod bollinger_bands;
 mod obv;
+mod gann_hilo;
+mod fractal;
+mod pipeline;
+mod series;
+mod macd;
+mod atr;
+mod stochastic;
+mod vwap;
+mod donchian;
+mod keltner;
+mod adx;
+mod cci;
+mod williams_r;
+mod psar;
+mod roc;
+mod momentum;
+mod std_dev;
+mod crossover;
 
+pub use rolling_window::{RollingMoments, RollingWindow};
+pub use price_source::PriceSource;
 pub use sma::SimpleMovingAverage;
 pub use ema::ExponentialMovingAverage;
 pub use rsi::RSI;
 pub use bollinger_bands::{BollingerBands, BollingerBandsOutput};
-pub use obv::OnBalanceVolume;
\ No newline at end of file
+pub use obv::OnBalanceVolume;
+pub use gann_hilo::{GannHiLoActivator, GannHiLoOutput};
+pub use fractal::{Fractal, FractalOutput};
+pub use pipeline::IndicatorPipeline;
+pub use series::IndicatorSeries;
+pub use macd::{MACD, MacdOutput};
+pub use atr::ATR;
+pub use stochastic::{StochasticOscillator, StochasticOutput};
+pub use vwap::VWAP;
+pub use donchian::{DonchianChannel, DonchianOutput};
+pub use keltner::{KeltnerChannel, KeltnerOutput};
+pub use adx::{ADX, AdxOutput};
+pub use cci::CCI;
+pub use williams_r::WilliamsR;
+pub use psar::ParabolicSAR;
+pub use roc::RateOfChange;
+pub use momentum::Momentum;
+pub use std_dev::StandardDeviation;
+pub use crossover::Crossover;
\ No newline at end of file
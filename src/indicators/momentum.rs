@@ -0,0 +1,41 @@
+use crate::types::OHLCV;
+use super::{Indicator, RollingWindow};
+
+/// Momentum: the change of the close over the last `period` bars, in price units.
+/// `None` until `period + 1` closes are available.
+pub struct Momentum {
+    closes: RollingWindow,
+    current_value: Option<f64>,
+}
+
+impl Momentum {
+    pub fn new(period: usize) -> Self {
+        Self {
+            closes: RollingWindow::new(period + 1),
+            current_value: None,
+        }
+    }
+}
+
+impl Indicator for Momentum {
+    type Output = f64;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<f64> {
+        self.closes.push(bar.close);
+        if !self.closes.is_full() {
+            return None;
+        }
+        
+        self.current_value = Some(bar.close - self.closes.oldest()?);
+        self.current()
+    }
+    
+    fn current(&self) -> Option<f64> {
+        self.current_value
+    }
+    
+    fn reset(&mut self) {
+        self.closes.clear();
+        self.current_value = None;
+    }
+}
\ No newline at end of file
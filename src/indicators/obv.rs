@@ -44,4 +44,9 @@ impl Indicator for OnBalanceVolume {
             None
         }
     }
+    
+    fn reset(&mut self) {
+        self.prev_close = None;
+        self.obv_value = 0.0;
+    }
 }
\ No newline at end of file
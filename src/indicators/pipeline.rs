@@ -0,0 +1,114 @@
+use crate::types::OHLCV;
+use super::Indicator;
+
+/// Chain of `f64` indicators where each stage consumes the previous stage's output.
+///
+/// The first stage receives the raw bar; later stages receive a synthetic bar whose
+/// open, high, low and close are all the previous output. A stage only sees a value
+/// once every stage before it has produced one, so `None` propagates down the chain.
+#[derive(Default)]
+pub struct IndicatorPipeline {
+    stages: Vec<Box<dyn Indicator<Output = f64>>>,
+    value: Option<f64>,
+}
+
+impl IndicatorPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a stage fed by the output of the current last stage
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<I: Indicator<Output = f64> + 'static>(mut self, stage: I) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+}
+
+impl Indicator for IndicatorPipeline {
+    type Output = f64;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<f64> {
+        let mut stages = self.stages.iter_mut();
+        let mut value = match stages.next() {
+            Some(first) => first.update(bar),
+            None => Some(bar.close),
+        };
+
+        for stage in stages {
+            let Some(input) = value else {
+                break;
+            };
+            value = stage.update(&OHLCV {
+                timestamp: bar.timestamp,
+                open: input,
+                high: input,
+                low: input,
+                close: input,
+                volume: bar.volume,
+            });
+        }
+
+        self.value = value;
+        self.value
+    }
+    
+    fn current(&self) -> Option<f64> {
+        self.value
+    }
+    
+    fn reset(&mut self) {
+        for stage in &mut self.stages {
+            stage.reset();
+        }
+        self.value = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::{SimpleMovingAverage, RSI};
+    use crate::test_util::flat_bars;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn matches_feeding_one_indicator_into_the_next_by_hand() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let closes: Vec<f64> = (0..80).map(|_| 100.0 + rng.gen_range(-5.0..5.0)).collect();
+
+        let mut pipeline = IndicatorPipeline::new()
+            .add(RSI::new(14))
+            .add(SimpleMovingAverage::new(3));
+        let mut rsi = RSI::new(14);
+        let mut smoothing = SimpleMovingAverage::new(3);
+
+        let (mut first_rsi, mut first_output) = (None, None);
+        for (i, bar) in flat_bars(&closes).into_iter().enumerate() {
+            let rsi_value = rsi.update(&bar);
+            let by_hand = rsi_value.and_then(|value| {
+                smoothing.update(&OHLCV {
+                    open: value,
+                    high: value,
+                    low: value,
+                    close: value,
+                    ..bar.clone()
+                })
+            });
+            assert_eq!(pipeline.update(&bar), by_hand);
+            first_rsi = first_rsi.or(rsi_value.map(|_| i));
+            first_output = first_output.or(by_hand.map(|_| i));
+        }
+        // The average needs three RSI values, so it starts two bars after the RSI
+        assert_eq!(first_output, first_rsi.map(|i| i + 2));
+    }
+}
\ No newline at end of file
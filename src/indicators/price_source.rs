@@ -0,0 +1,32 @@
+use crate::types::OHLCV;
+use serde::{Deserialize, Serialize};
+
+/// Which value of a bar an indicator reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PriceSource {
+    Open,
+    High,
+    Low,
+    #[default]
+    Close,
+    /// `(high + low) / 2`
+    Median,
+    /// `(high + low + close) / 3`
+    Typical,
+    /// `(high + low + 2 * close) / 4`
+    Weighted,
+}
+
+impl PriceSource {
+    pub fn value(&self, bar: &OHLCV) -> f64 {
+        match self {
+            PriceSource::Open => bar.open,
+            PriceSource::High => bar.high,
+            PriceSource::Low => bar.low,
+            PriceSource::Close => bar.close,
+            PriceSource::Median => (bar.high + bar.low) / 2.0,
+            PriceSource::Typical => (bar.high + bar.low + bar.close) / 3.0,
+            PriceSource::Weighted => (bar.high + bar.low + 2.0 * bar.close) / 4.0,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,160 @@
+use crate::types::OHLCV;
+use super::Indicator;
+
+/// Parabolic SAR (stop and reverse).
+///
+/// The SAR trails price, moving towards the trend's extreme point by an acceleration
+/// factor that starts at `step` and grows by `step` at each new extreme, up to
+/// `max_step`. In an uptrend it never rises above the previous two lows (below the
+/// previous two highs in a downtrend); when price crosses it the trend flips and the
+/// SAR restarts at the old extreme point.
+///
+/// The first bar only seeds the state; the second sets the initial trend from the
+/// change in close and is the first bar with a value.
+pub struct ParabolicSAR {
+    step: f64,
+    max_step: f64,
+    prev_bars: Option<((f64, f64), (f64, f64))>,
+    prev_close: Option<f64>,
+    is_long: bool,
+    extreme_point: f64,
+    acceleration: f64,
+    current_value: Option<f64>,
+}
+
+impl ParabolicSAR {
+    pub fn new(step: f64, max_step: f64) -> Self {
+        Self {
+            step,
+            max_step,
+            prev_bars: None,
+            prev_close: None,
+            is_long: true,
+            extreme_point: 0.0,
+            acceleration: step,
+            current_value: None,
+        }
+    }
+    
+    /// Whether the SAR is currently below price, tracking an uptrend
+    pub fn is_long(&self) -> bool {
+        self.is_long
+    }
+}
+
+impl Indicator for ParabolicSAR {
+    type Output = f64;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<f64> {
+        let Some(prev_close) = self.prev_close.replace(bar.close) else {
+            self.prev_bars = Some(((bar.high, bar.low), (bar.high, bar.low)));
+            return None;
+        };
+        let ((prev_high, prev_low), (prev2_high, prev2_low)) = self.prev_bars?;
+        self.prev_bars = Some(((bar.high, bar.low), (prev_high, prev_low)));
+        
+        let Some(sar) = self.current_value else {
+            // Second bar: start the trend in the direction of the close
+            self.is_long = bar.close >= prev_close;
+            self.acceleration = self.step;
+            let sar = if self.is_long {
+                self.extreme_point = bar.high.max(prev_high);
+                bar.low.min(prev_low)
+            } else {
+                self.extreme_point = bar.low.min(prev_low);
+                bar.high.max(prev_high)
+            };
+            self.current_value = Some(sar);
+            return self.current();
+        };
+        
+        let mut next_sar = sar + self.acceleration * (self.extreme_point - sar);
+        if self.is_long {
+            next_sar = next_sar.min(prev_low).min(prev2_low);
+            if bar.low < next_sar {
+                self.is_long = false;
+                next_sar = self.extreme_point;
+                self.extreme_point = bar.low;
+                self.acceleration = self.step;
+            } else if bar.high > self.extreme_point {
+                self.extreme_point = bar.high;
+                self.acceleration = (self.acceleration + self.step).min(self.max_step);
+            }
+        } else {
+            next_sar = next_sar.max(prev_high).max(prev2_high);
+            if bar.high > next_sar {
+                self.is_long = true;
+                next_sar = self.extreme_point;
+                self.extreme_point = bar.high;
+                self.acceleration = self.step;
+            } else if bar.low < self.extreme_point {
+                self.extreme_point = bar.low;
+                self.acceleration = (self.acceleration + self.step).min(self.max_step);
+            }
+        }
+        
+        self.current_value = Some(next_sar);
+        self.current()
+    }
+    
+    fn current(&self) -> Option<f64> {
+        self.current_value
+    }
+    
+    fn reset(&mut self) {
+        self.prev_bars = None;
+        self.prev_close = None;
+        self.is_long = true;
+        self.extreme_point = 0.0;
+        self.acceleration = self.step;
+        self.current_value = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::bars;
+
+    #[test]
+    fn matches_a_hand_worked_reference_series() {
+        // (high, low, close): a rally to 13 that reverses into a sell-off
+        let hlc = [
+            (10.0, 9.0, 9.5),
+            (11.0, 9.5, 10.8),
+            (12.0, 10.5, 11.8),
+            (13.0, 11.5, 12.8),
+            (12.5, 11.0, 11.2),
+            (11.0, 9.0, 9.2),
+            (10.0, 8.5, 8.8),
+            (9.5, 8.0, 8.2),
+        ];
+        let prices: Vec<_> = hlc.iter().map(|&(h, l, c)| (c, h, l, c)).collect();
+
+        // Long from the second bar, with bar 2's SAR capped at the low two bars back.
+        // Bar 5 trades through the SAR and flips it short at the extreme point of 13.
+        let expected = [
+            None,
+            Some(9.0),
+            Some(9.0),
+            Some(9.12),
+            Some(9.3528),
+            Some(13.0),
+            Some(12.92),
+            Some(12.7432),
+        ];
+        let trend = [true, true, true, true, true, false, false, false];
+
+        let mut sar = ParabolicSAR::new(0.02, 0.2);
+        for (i, bar) in bars(&prices).iter().enumerate() {
+            let value = sar.update(bar);
+            match (value, expected[i]) {
+                (Some(value), Some(expected)) => {
+                    assert!((value - expected).abs() < 1e-9, "bar {i}: {value}")
+                }
+                (value, expected) => assert_eq!(value, expected, "bar {i}"),
+            }
+            assert_eq!(sar.is_long(), trend[i], "bar {i}");
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,44 @@
+use crate::types::OHLCV;
+use super::{Indicator, RollingWindow};
+
+/// Rate of change: the percentage change of the close over the last `period` bars.
+///
+/// `None` until `period + 1` closes are available, and while the close `period` bars
+/// ago is zero.
+pub struct RateOfChange {
+    closes: RollingWindow,
+    current_value: Option<f64>,
+}
+
+impl RateOfChange {
+    pub fn new(period: usize) -> Self {
+        Self {
+            closes: RollingWindow::new(period + 1),
+            current_value: None,
+        }
+    }
+}
+
+impl Indicator for RateOfChange {
+    type Output = f64;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<f64> {
+        self.closes.push(bar.close);
+        if !self.closes.is_full() {
+            return None;
+        }
+        
+        let base = self.closes.oldest()?;
+        self.current_value = (base != 0.0).then(|| (bar.close - base) / base * 100.0);
+        self.current()
+    }
+    
+    fn current(&self) -> Option<f64> {
+        self.current_value
+    }
+    
+    fn reset(&mut self) {
+        self.closes.clear();
+        self.current_value = None;
+    }
+}
\ No newline at end of file
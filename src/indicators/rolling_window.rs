@@ -0,0 +1,286 @@
+use std::collections::VecDeque;
+
+/// Fixed-capacity window over the most recent values.
+///
+/// Every update is O(1): the sum is maintained incrementally for `mean()`, and
+/// monotonic candidate queues keep `min()`/`max()` amortised O(1) as well.
+#[derive(Debug, Clone)]
+pub struct RollingWindow {
+    capacity: usize,
+    values: VecDeque<f64>,
+    sum: f64,
+    pushed: usize,
+    min_candidates: VecDeque<(usize, f64)>,
+    max_candidates: VecDeque<(usize, f64)>,
+}
+
+impl RollingWindow {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            values: VecDeque::with_capacity(capacity + 1),
+            sum: 0.0,
+            pushed: 0,
+            min_candidates: VecDeque::new(),
+            max_candidates: VecDeque::new(),
+        }
+    }
+
+    /// Push a new value, returning the value evicted from the window if it was full
+    pub fn push(&mut self, value: f64) -> Option<f64> {
+        self.values.push_back(value);
+        self.sum += value;
+
+        let index = self.pushed;
+        self.pushed += 1;
+
+        while matches!(self.min_candidates.back(), Some(&(_, v)) if v >= value) {
+            self.min_candidates.pop_back();
+        }
+        self.min_candidates.push_back((index, value));
+
+        while matches!(self.max_candidates.back(), Some(&(_, v)) if v <= value) {
+            self.max_candidates.pop_back();
+        }
+        self.max_candidates.push_back((index, value));
+
+        let evicted = if self.values.len() > self.capacity {
+            let old_value = self.values.pop_front().unwrap();
+            self.sum -= old_value;
+            Some(old_value)
+        } else {
+            None
+        };
+
+        // Drop candidates that have slid out of the window
+        let oldest_index = self.pushed - self.values.len();
+        while matches!(self.min_candidates.front(), Some(&(i, _)) if i < oldest_index) {
+            self.min_candidates.pop_front();
+        }
+        while matches!(self.max_candidates.front(), Some(&(i, _)) if i < oldest_index) {
+            self.max_candidates.pop_front();
+        }
+
+        evicted
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Whether the window holds `capacity` values
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.capacity
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.values.is_empty() {
+            None
+        } else {
+            Some(self.sum / self.values.len() as f64)
+        }
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.min_candidates.front().map(|&(_, v)| v)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.max_candidates.front().map(|&(_, v)| v)
+    }
+
+    /// Most recently pushed value
+    pub fn newest(&self) -> Option<f64> {
+        self.values.back().copied()
+    }
+
+    /// Oldest value still inside the window
+    pub fn oldest(&self) -> Option<f64> {
+        self.values.front().copied()
+    }
+
+    /// Iterate from the oldest to the newest value
+    pub fn iter(&self) -> impl Iterator<Item = &f64> {
+        self.values.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.sum = 0.0;
+        self.pushed = 0;
+        self.min_candidates.clear();
+        self.max_candidates.clear();
+    }
+}
+
+/// Rolling window that also tracks the mean and variance of its values.
+///
+/// The running sums are kept relative to a shift near the window's mean, so prices far
+/// from zero don't lose their variance to cancellation, and they are recomputed from the
+/// window every `capacity` pushes so rounding can't accumulate over a long series.
+/// Updates stay amortised O(1).
+#[derive(Debug, Clone)]
+pub struct RollingMoments {
+    window: RollingWindow,
+    shift: f64,
+    shifted_sum: f64,
+    shifted_sum_squares: f64,
+    pushes_since_resum: usize,
+}
+
+impl RollingMoments {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            window: RollingWindow::new(capacity),
+            shift: 0.0,
+            shifted_sum: 0.0,
+            shifted_sum_squares: 0.0,
+            pushes_since_resum: 0,
+        }
+    }
+
+    /// Push a new value, returning the value evicted from the window if it was full
+    pub fn push(&mut self, value: f64) -> Option<f64> {
+        if self.window.is_empty() {
+            self.shift = value;
+        }
+
+        let evicted = self.window.push(value);
+        let shifted = value - self.shift;
+        self.shifted_sum += shifted;
+        self.shifted_sum_squares += shifted * shifted;
+        if let Some(old_value) = evicted {
+            let shifted = old_value - self.shift;
+            self.shifted_sum -= shifted;
+            self.shifted_sum_squares -= shifted * shifted;
+        }
+
+        self.pushes_since_resum += 1;
+        if self.pushes_since_resum >= self.window.capacity() {
+            self.resum();
+        }
+
+        evicted
+    }
+
+    /// Re-centre the shift on the window's mean and recompute the sums from scratch
+    fn resum(&mut self) {
+        if let Some(mean) = self.mean() {
+            self.shift = mean;
+        }
+        let shift = self.shift;
+        self.shifted_sum = self.window.iter().map(|v| v - shift).sum();
+        self.shifted_sum_squares = self.window.iter().map(|v| (v - shift).powi(2)).sum();
+        self.pushes_since_resum = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    /// Whether the window holds `capacity` values
+    pub fn is_full(&self) -> bool {
+        self.window.is_full()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.window.is_empty() {
+            None
+        } else {
+            Some(self.shift + self.shifted_sum / self.window.len() as f64)
+        }
+    }
+
+    /// Population variance of the window, or the sample variance with Bessel's
+    /// correction when `sample` is set. `None` until there are enough values.
+    pub fn variance(&self, sample: bool) -> Option<f64> {
+        let n = self.window.len() as f64;
+        let divisor = if sample { n - 1.0 } else { n };
+        if divisor <= 0.0 {
+            return None;
+        }
+
+        let squared_deviations = self.shifted_sum_squares - self.shifted_sum * self.shifted_sum / n;
+        // Rounding can push a flat window's variance just below zero
+        Some((squared_deviations / divisor).max(0.0))
+    }
+
+    pub fn clear(&mut self) {
+        self.window.clear();
+        self.shift = 0.0;
+        self.shifted_sum = 0.0;
+        self.shifted_sum_squares = 0.0;
+        self.pushes_since_resum = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_and_max_move_on_when_the_extreme_is_evicted() {
+        let mut window = RollingWindow::new(3);
+        for value in [5.0, 1.0, 9.0] {
+            window.push(value);
+        }
+        assert_eq!((window.min(), window.max()), (Some(1.0), Some(9.0)));
+
+        // Evicting the 5 leaves the extremes alone
+        assert_eq!(window.push(4.0), Some(5.0));
+        assert_eq!((window.min(), window.max()), (Some(1.0), Some(9.0)));
+
+        // Evicting the minimum hands it to the smallest value still in the window
+        assert_eq!(window.push(6.0), Some(1.0));
+        assert_eq!((window.min(), window.max()), (Some(4.0), Some(9.0)));
+
+        // And likewise for the maximum
+        assert_eq!(window.push(2.0), Some(9.0));
+        assert_eq!((window.min(), window.max()), (Some(2.0), Some(6.0)));
+    }
+
+    #[test]
+    fn tied_extremes_survive_the_eviction_of_one_copy() {
+        let mut window = RollingWindow::new(3);
+        for value in [7.0, 3.0, 7.0, 3.0] {
+            window.push(value);
+        }
+        // [3, 7, 3]: the first 3 leaving on the next push still leaves one behind
+        assert_eq!((window.min(), window.max()), (Some(3.0), Some(7.0)));
+        window.push(5.0);
+        assert_eq!((window.min(), window.max()), (Some(3.0), Some(7.0)));
+        window.push(5.0);
+        assert_eq!((window.min(), window.max()), (Some(3.0), Some(5.0)));
+        window.push(5.0);
+        assert_eq!((window.min(), window.max()), (Some(5.0), Some(5.0)));
+    }
+
+    #[test]
+    fn min_and_max_match_a_scan_of_the_window() {
+        let values = [4.0, 4.0, 2.0, 8.0, 8.0, 1.0, 3.0, 3.0, 9.0, 0.0, 0.0, 5.0];
+        let mut window = RollingWindow::new(4);
+        for (i, &value) in values.iter().enumerate() {
+            window.push(value);
+            let held = &values[i.saturating_sub(3)..=i];
+            let min = held.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = held.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            assert_eq!((window.min(), window.max()), (Some(min), Some(max)), "{i}");
+        }
+    }
+}
@@ -1,26 +1,38 @@
 use crate::types::OHLCV;
-use super::Indicator;
+use super::{Indicator, PriceSource};
 
+/// Wilder's Relative Strength Index.
+///
+/// The first `period` changes are summed to seed the average gain and loss; after
+/// that only the smoothed averages are kept, so each update is O(1).
 pub struct RSI {
     period: usize,
-    gains: Vec<f64>,
-    losses: Vec<f64>,
+    warmup_count: usize,
+    gain_sum: f64,
+    loss_sum: f64,
     avg_gain: f64,
     avg_loss: f64,
     prev_close: Option<f64>,
     is_initialized: bool,
+    source: PriceSource,
 }
 
 impl RSI {
     pub fn new(period: usize) -> Self {
+        Self::with_source(period, PriceSource::Close)
+    }
+
+    pub fn with_source(period: usize, source: PriceSource) -> Self {
         Self {
             period,
-            gains: Vec::new(),
-            losses: Vec::new(),
+            warmup_count: 0,
+            gain_sum: 0.0,
+            loss_sum: 0.0,
             avg_gain: 0.0,
             avg_loss: 0.0,
             prev_close: None,
             is_initialized: false,
+            source,
         }
     }
 }
@@ -29,49 +41,40 @@ impl Indicator for RSI {
     type Output = f64;
     
     fn update(&mut self, bar: &OHLCV) -> Option<f64> {
+        let price = self.source.value(bar);
         let Some(prev_close) = self.prev_close else {
-            self.prev_close = Some(bar.close);
+            self.prev_close = Some(price);
             return None;
         };
         
-        let change = bar.close - prev_close;
+        let change = price - prev_close;
         let gain = if change > 0.0 { change } else { 0.0 };
         let loss = if change < 0.0 { -change } else { 0.0 };
         
-        self.gains.push(gain);
-        self.losses.push(loss);
-        
-        if self.gains.len() > self.period {
-            self.gains.remove(0);
-            self.losses.remove(0);
-        }
-        
-        self.prev_close = Some(bar.close);
+        self.prev_close = Some(price);
         
-        if self.gains.len() == self.period {
-            if !self.is_initialized {
-                self.avg_gain = self.gains.iter().sum::<f64>() / self.period as f64;
-                self.avg_loss = self.losses.iter().sum::<f64>() / self.period as f64;
-                self.is_initialized = true;
-            } else {
-                self.avg_gain = (self.avg_gain * (self.period - 1) as f64 + gain) / self.period as f64;
-                self.avg_loss = (self.avg_loss * (self.period - 1) as f64 + loss) / self.period as f64;
-            }
+        if self.is_initialized {
+            self.avg_gain = (self.avg_gain * (self.period - 1) as f64 + gain) / self.period as f64;
+            self.avg_loss = (self.avg_loss * (self.period - 1) as f64 + loss) / self.period as f64;
+        } else {
+            self.gain_sum += gain;
+            self.loss_sum += loss;
+            self.warmup_count += 1;
             
-            if self.avg_loss == 0.0 {
-                return Some(100.0);
+            if self.warmup_count < self.period {
+                return None;
             }
             
-            let rs = self.avg_gain / self.avg_loss;
-            let rsi = 100.0 - (100.0 / (1.0 + rs));
-            Some(rsi)
-        } else {
-            None
+            self.avg_gain = self.gain_sum / self.period as f64;
+            self.avg_loss = self.loss_sum / self.period as f64;
+            self.is_initialized = true;
         }
+        
+        self.current()
     }
     
     fn current(&self) -> Option<f64> {
-        if self.gains.len() == self.period && self.is_initialized {
+        if self.is_initialized {
             if self.avg_loss == 0.0 {
                 return Some(100.0);
             }
@@ -82,4 +85,14 @@ impl Indicator for RSI {
             None
         }
     }
+    
+    fn reset(&mut self) {
+        self.warmup_count = 0;
+        self.gain_sum = 0.0;
+        self.loss_sum = 0.0;
+        self.avg_gain = 0.0;
+        self.avg_loss = 0.0;
+        self.prev_close = None;
+        self.is_initialized = false;
+    }
 }
\ No newline at end of file
@@ -0,0 +1,49 @@
+use crate::types::OHLCV;
+use super::Indicator;
+
+/// Indicator outputs precomputed over a whole data set, aligned to its bars.
+///
+/// Build it in `Strategy::init` and index it by the bar index passed to `next`.
+/// Each value only depends on bars up to its own, so lookups never peek ahead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndicatorSeries<T> {
+    values: Vec<Option<T>>,
+}
+
+impl<T> IndicatorSeries<T> {
+    /// Replay `indicator` over every bar of `data`, storing each output
+    pub fn compute<I: Indicator<Output = T>>(mut indicator: I, data: &[OHLCV]) -> Self {
+        Self {
+            values: data.iter().map(|bar| indicator.update(bar)).collect(),
+        }
+    }
+
+    /// Output at bar `index`, `None` while warming up or past the end of the data
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.values.get(index)?.as_ref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn values(&self) -> &[Option<T>] {
+        &self.values
+    }
+
+    pub fn into_values(self) -> Vec<Option<T>> {
+        self.values
+    }
+}
+
+impl<T> std::ops::Index<usize> for IndicatorSeries<T> {
+    type Output = Option<T>;
+
+    fn index(&self, index: usize) -> &Option<T> {
+        &self.values[index]
+    }
+}
\ No newline at end of file
@@ -1,19 +1,20 @@
 use crate::types::OHLCV;
-use super::Indicator;
-use std::collections::VecDeque;
+use super::{Indicator, PriceSource, RollingWindow};
 
 pub struct SimpleMovingAverage {
-    window: usize,
-    values: VecDeque<f64>,
-    sum: f64,
+    window: RollingWindow,
+    source: PriceSource,
 }
 
 impl SimpleMovingAverage {
     pub fn new(window: usize) -> Self {
+        Self::with_source(window, PriceSource::Close)
+    }
+
+    pub fn with_source(window: usize, source: PriceSource) -> Self {
         Self {
-            window,
-            values: VecDeque::with_capacity(window),
-            sum: 0.0,
+            window: RollingWindow::new(window),
+            source,
         }
     }
 }
@@ -22,26 +23,19 @@ impl Indicator for SimpleMovingAverage {
     type Output = f64;
     
     fn update(&mut self, bar: &OHLCV) -> Option<f64> {
-        self.values.push_back(bar.close);
-        self.sum += bar.close;
-        
-        if self.values.len() > self.window {
-            let old_value = self.values.pop_front().unwrap();
-            self.sum -= old_value;
-        }
-        
-        if self.values.len() == self.window {
-            Some(self.sum / self.window as f64)
-        } else {
-            None
-        }
+        self.window.push(self.source.value(bar));
+        self.current()
     }
     
     fn current(&self) -> Option<f64> {
-        if self.values.len() == self.window {
-            Some(self.sum / self.window as f64)
+        if self.window.is_full() {
+            self.window.mean()
         } else {
             None
         }
     }
+    
+    fn reset(&mut self) {
+        self.window.clear();
+    }
 }
\ No newline at end of file
@@ -0,0 +1,104 @@
+use crate::types::OHLCV;
+use super::{Indicator, PriceSource, RollingMoments};
+
+/// Rolling standard deviation of a price over the last `period` bars.
+///
+/// The window's mean and variance are updated incrementally, so each bar is amortised
+/// O(1) and stays accurate over long series of high prices.
+/// `new` gives the population deviation (divides by `period`, as Bollinger Bands do);
+/// `sample` divides by `period - 1`.
+pub struct StandardDeviation {
+    values: RollingMoments,
+    sample: bool,
+    source: PriceSource,
+    current_value: Option<f64>,
+}
+
+impl StandardDeviation {
+    pub fn new(period: usize) -> Self {
+        Self::with_source(period, false, PriceSource::Close)
+    }
+
+    /// Sample standard deviation, with Bessel's correction
+    pub fn sample(period: usize) -> Self {
+        Self::with_source(period, true, PriceSource::Close)
+    }
+
+    pub fn with_source(period: usize, sample: bool, source: PriceSource) -> Self {
+        Self {
+            values: RollingMoments::new(period),
+            sample,
+            source,
+            current_value: None,
+        }
+    }
+    
+    /// Variance of the current window, the square of `current`
+    pub fn variance(&self) -> Option<f64> {
+        self.current_value.map(|std_dev| std_dev * std_dev)
+    }
+}
+
+impl Indicator for StandardDeviation {
+    type Output = f64;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<f64> {
+        self.values.push(self.source.value(bar));
+        if !self.values.is_full() {
+            return None;
+        }
+        
+        let variance = self.values.variance(self.sample).unwrap_or(0.0);
+        self.current_value = Some(variance.sqrt());
+        self.current()
+    }
+    
+    fn current(&self) -> Option<f64> {
+        self.current_value
+    }
+    
+    fn reset(&mut self) {
+        self.values.clear();
+        self.current_value = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::flat_bars;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Standard deviation of `window` summed from scratch, dividing by `n - ddof`
+    fn naive(window: &[f64], ddof: f64) -> f64 {
+        let n = window.len() as f64;
+        let mean = window.iter().sum::<f64>() / n;
+        let squared_deviations: f64 = window.iter().map(|v| (v - mean).powi(2)).sum();
+        (squared_deviations / (n - ddof)).sqrt()
+    }
+
+    #[test]
+    fn streaming_deviation_matches_naive_population_and_sample() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let closes: Vec<f64> = (0..20_000)
+            .map(|_| 50_000.0 + rng.gen_range(-2.0..2.0))
+            .collect();
+        let period = 30;
+        let mut population = StandardDeviation::new(period);
+        let mut sample = StandardDeviation::sample(period);
+
+        for (i, bar) in flat_bars(&closes).iter().enumerate() {
+            let (population_value, sample_value) = (population.update(bar), sample.update(bar));
+            if i + 1 < period {
+                assert!(population_value.is_none() && sample_value.is_none());
+                continue;
+            }
+
+            let window = &closes[i + 1 - period..=i];
+            let population_error = (population_value.unwrap() - naive(window, 0.0)).abs();
+            let sample_error = (sample_value.unwrap() - naive(window, 1.0)).abs();
+            assert!(population_error < 1e-9 && sample_error < 1e-9, "bar {i}");
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,82 @@
+use crate::types::OHLCV;
+use super::{Indicator, RollingWindow};
+
+#[derive(Debug, Clone)]
+pub struct StochasticOutput {
+    pub k: f64,
+    pub d: f64,
+}
+
+/// Stochastic oscillator.
+///
+/// Raw %K is where the close sits within the highest high and lowest low of the last
+/// `k_period` bars (0-100, 50 when the range is flat). %K is the SMA of raw %K over
+/// `smoothing` bars (1 gives the fast oscillator) and %D is the SMA of %K over `d_period`.
+pub struct StochasticOscillator {
+    highs: RollingWindow,
+    lows: RollingWindow,
+    raw_k: RollingWindow,
+    k: RollingWindow,
+    current_value: Option<StochasticOutput>,
+}
+
+impl StochasticOscillator {
+    pub fn new(k_period: usize, d_period: usize, smoothing: usize) -> Self {
+        Self {
+            highs: RollingWindow::new(k_period),
+            lows: RollingWindow::new(k_period),
+            raw_k: RollingWindow::new(smoothing.max(1)),
+            k: RollingWindow::new(d_period),
+            current_value: None,
+        }
+    }
+}
+
+impl Indicator for StochasticOscillator {
+    type Output = StochasticOutput;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<StochasticOutput> {
+        self.highs.push(bar.high);
+        self.lows.push(bar.low);
+        if !self.highs.is_full() {
+            return None;
+        }
+        
+        let highest = self.highs.max()?;
+        let lowest = self.lows.min()?;
+        let raw_k = if highest > lowest {
+            100.0 * (bar.close - lowest) / (highest - lowest)
+        } else {
+            50.0
+        };
+        
+        self.raw_k.push(raw_k);
+        if !self.raw_k.is_full() {
+            return None;
+        }
+        
+        let k = self.raw_k.mean()?;
+        self.k.push(k);
+        if !self.k.is_full() {
+            return None;
+        }
+        
+        self.current_value = Some(StochasticOutput {
+            k,
+            d: self.k.mean()?,
+        });
+        self.current()
+    }
+    
+    fn current(&self) -> Option<StochasticOutput> {
+        self.current_value.clone()
+    }
+    
+    fn reset(&mut self) {
+        self.highs.clear();
+        self.lows.clear();
+        self.raw_k.clear();
+        self.k.clear();
+        self.current_value = None;
+    }
+}
\ No newline at end of file
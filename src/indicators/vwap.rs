@@ -0,0 +1,79 @@
+use crate::types::OHLCV;
+use super::Indicator;
+use chrono::{Duration, NaiveDate};
+
+/// Volume Weighted Average Price of the typical price `(high + low + close) / 3`.
+///
+/// `VWAP::new()` accumulates over the whole series. `VWAP::session_based(reset_hour)`
+/// starts over on the first bar of each session, where sessions run from `reset_hour`
+/// UTC to the same hour on the next day. Until any volume has traded the typical price
+/// is returned.
+pub struct VWAP {
+    reset_hour: Option<u32>,
+    session: Option<NaiveDate>,
+    price_volume: f64,
+    volume: f64,
+    current_value: Option<f64>,
+}
+
+impl VWAP {
+    pub fn new() -> Self {
+        Self {
+            reset_hour: None,
+            session: None,
+            price_volume: 0.0,
+            volume: 0.0,
+            current_value: None,
+        }
+    }
+
+    pub fn session_based(reset_hour: u32) -> Self {
+        Self {
+            reset_hour: Some(reset_hour),
+            ..Self::new()
+        }
+    }
+}
+
+impl Default for VWAP {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Indicator for VWAP {
+    type Output = f64;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<f64> {
+        if let Some(reset_hour) = self.reset_hour {
+            let session = (bar.timestamp - Duration::hours(reset_hour as i64)).date_naive();
+            if self.session != Some(session) {
+                self.session = Some(session);
+                self.price_volume = 0.0;
+                self.volume = 0.0;
+            }
+        }
+        
+        let typical_price = (bar.high + bar.low + bar.close) / 3.0;
+        self.price_volume += typical_price * bar.volume;
+        self.volume += bar.volume;
+        
+        self.current_value = Some(if self.volume > 0.0 {
+            self.price_volume / self.volume
+        } else {
+            typical_price
+        });
+        self.current()
+    }
+    
+    fn current(&self) -> Option<f64> {
+        self.current_value
+    }
+    
+    fn reset(&mut self) {
+        self.session = None;
+        self.price_volume = 0.0;
+        self.volume = 0.0;
+        self.current_value = None;
+    }
+}
\ No newline at end of file
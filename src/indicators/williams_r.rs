@@ -0,0 +1,53 @@
+use crate::types::OHLCV;
+use super::{Indicator, RollingWindow};
+
+/// Williams %R.
+///
+/// Where the close sits below the highest high of the last `period` bars, relative to
+/// the high-low range: 0 at the high, -100 at the low and -50 when the range is flat.
+pub struct WilliamsR {
+    highs: RollingWindow,
+    lows: RollingWindow,
+    current_value: Option<f64>,
+}
+
+impl WilliamsR {
+    pub fn new(period: usize) -> Self {
+        Self {
+            highs: RollingWindow::new(period),
+            lows: RollingWindow::new(period),
+            current_value: None,
+        }
+    }
+}
+
+impl Indicator for WilliamsR {
+    type Output = f64;
+    
+    fn update(&mut self, bar: &OHLCV) -> Option<f64> {
+        self.highs.push(bar.high);
+        self.lows.push(bar.low);
+        if !self.highs.is_full() {
+            return None;
+        }
+        
+        let highest = self.highs.max()?;
+        let lowest = self.lows.min()?;
+        self.current_value = Some(if highest > lowest {
+            (highest - bar.close) / (highest - lowest) * -100.0
+        } else {
+            -50.0
+        });
+        self.current()
+    }
+    
+    fn current(&self) -> Option<f64> {
+        self.current_value
+    }
+    
+    fn reset(&mut self) {
+        self.highs.clear();
+        self.lows.clear();
+        self.current_value = None;
+    }
+}
\ No newline at end of file
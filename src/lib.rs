@@ -6,6 +6,7 @@
 //! with support for optimization, plotting, and comprehensive performance analysis.
 
 pub mod backtest;
+pub mod error;
 pub mod strategy;
 pub mod types;
 pub mod order;
@@ -13,6 +14,7 @@ pub mod position;
 pub mod trade;
 pub mod indicators;
 pub mod data;
+pub mod portfolio;
 
 #[cfg(feature = "optimization")]
 pub mod optimization;
@@ -20,13 +22,17 @@ pub mod optimization;
 #[cfg(feature = "plotting")]
 pub mod plotting;
 
+#[cfg(test)]
+mod test_util;
+
 // Re-export main types for convenience
-pub use backtest::{Backtest, BacktestConfig, BacktestResults};
-pub use strategy::Strategy;
-pub use order::{Order, OrderType, OrderSide, OrderStatus};
-pub use position::Position;
+pub use backtest::{Backtest, BacktestCheckpoint, BacktestConfig, BacktestResults};
+pub use error::BacktestError;
+pub use strategy::{Context, Strategy};
+pub use order::{Order, OrderType, OrderSide, OrderStatus, SizeUnit};
+pub use position::{Lot, LotMatching, Position};
 pub use trade::Trade;
-pub use types::{OHLCV, DirectionalTrade, ProfitLoss, Closeable, Executable, StopManagement};
+pub use types::{OHLCV, CalendarPeriod, DirectionalTrade, ProfitLoss, Closeable, Executable, StopManagement};
 
 /// Result type used throughout the library
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
\ No newline at end of file
@@ -0,0 +1,135 @@
+use super::{compare_metrics, evaluate_combinations, OptimizationMetric, OptimizationResult};
+use crate::backtest::BacktestConfig;
+use crate::strategy::Strategy;
+use crate::types::OHLCV;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Evolves parameter combinations drawn from `parameter_ranges` instead of testing
+/// every one.
+///
+/// Each generation is backtested in parallel. The next generation keeps the best
+/// combination found so far and fills the rest with children of two tournament-selected
+/// parents: every parameter is inherited from either parent, then replaced by a random
+/// value from its range with probability `mutation_rate`.
+pub struct GeneticOptimizer {
+    pub population_size: usize,
+    pub generations: usize,
+    pub mutation_rate: f64,
+    pub seed: u64,
+}
+
+impl GeneticOptimizer {
+    pub fn new(population_size: usize, generations: usize) -> Self {
+        Self {
+            population_size,
+            generations,
+            mutation_rate: 0.1,
+            seed: 0,
+        }
+    }
+
+    pub fn with_mutation_rate(mut self, mutation_rate: f64) -> Self {
+        self.mutation_rate = mutation_rate;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn optimize<S, F, M>(
+        &self,
+        data: &[OHLCV],
+        config: &BacktestConfig,
+        strategy_factory: F,
+        parameter_ranges: HashMap<String, Vec<f64>>,
+        metric: M,
+    ) -> crate::Result<OptimizationResult>
+    where
+        S: Strategy + Send,
+        F: Fn(&HashMap<String, f64>) -> S + Send + Sync,
+        M: OptimizationMetric + Send + Sync,
+    {
+        if parameter_ranges.values().any(|values| values.is_empty()) {
+            return Err("Every parameter needs at least one value".into());
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        // Sort the names so a seed gives the same run regardless of map order
+        let mut names: Vec<&String> = parameter_ranges.keys().collect();
+        names.sort();
+
+        let mut population: Vec<HashMap<String, f64>> = (0..self.population_size.max(1))
+            .map(|_| {
+                names
+                    .iter()
+                    .map(|&name| (name.clone(), random_value(&parameter_ranges[name], &mut rng)))
+                    .collect()
+            })
+            .collect();
+        let mut best: Option<OptimizationResult> = None;
+
+        for _ in 0..self.generations.max(1) {
+            let evaluated =
+                evaluate_combinations(data, config, &strategy_factory, population, &metric);
+            if evaluated.is_empty() {
+                break;
+            }
+
+            for result in &evaluated {
+                let improves = best.as_ref().is_none_or(|b| {
+                    compare_metrics(result.metric_value, b.metric_value) == Ordering::Greater
+                });
+                if improves {
+                    best = Some(result.clone());
+                }
+            }
+
+            population = Vec::with_capacity(self.population_size);
+            if let Some(ref best) = best {
+                population.push(best.parameters.clone());
+            }
+            while population.len() < self.population_size {
+                let first = tournament(&evaluated, &mut rng);
+                let second = tournament(&evaluated, &mut rng);
+                let child = names
+                    .iter()
+                    .map(|&name| {
+                        let value = if rng.gen::<f64>() < self.mutation_rate {
+                            random_value(&parameter_ranges[name], &mut rng)
+                        } else if rng.gen::<bool>() {
+                            first.parameters[name]
+                        } else {
+                            second.parameters[name]
+                        };
+                        (name.clone(), value)
+                    })
+                    .collect();
+                population.push(child);
+            }
+        }
+
+        best.ok_or_else(|| "No valid optimization results found".into())
+    }
+}
+
+fn random_value(values: &[f64], rng: &mut StdRng) -> f64 {
+    values[rng.gen_range(0..values.len())]
+}
+
+/// Better of two randomly picked results
+fn tournament<'a>(results: &'a [OptimizationResult], rng: &mut StdRng) -> &'a OptimizationResult {
+    let a = results.choose(rng).unwrap();
+    let b = results.choose(rng).unwrap();
+    if compare_metrics(a.metric_value, b.metric_value) == Ordering::Less {
+        b
+    } else {
+        a
+    }
+}
@@ -2,12 +2,27 @@ use crate::backtest::{Backtest, BacktestConfig, BacktestResults};
 use crate::strategy::Strategy;
 use crate::types::OHLCV;
 use rayon::prelude::*;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
+mod genetic;
+mod random_search;
+mod walk_forward;
+
+pub use genetic::GeneticOptimizer;
+pub use random_search::{ParameterSpace, RandomSearchOptimizer};
+pub use walk_forward::{WalkForwardOptimizer, WalkForwardSegment};
+
 pub trait OptimizationMetric {
     fn calculate(&self, results: &BacktestResults) -> f64;
 }
 
+impl<M: OptimizationMetric + ?Sized> OptimizationMetric for &M {
+    fn calculate(&self, results: &BacktestResults) -> f64 {
+        (**self).calculate(results)
+    }
+}
+
 pub struct SharpeRatio;
 impl OptimizationMetric for SharpeRatio {
     fn calculate(&self, results: &BacktestResults) -> f64 {
@@ -29,6 +44,40 @@ impl OptimizationMetric for ProfitFactor {
     }
 }
 
+pub struct CalmarRatio;
+impl OptimizationMetric for CalmarRatio {
+    fn calculate(&self, results: &BacktestResults) -> f64 {
+        results.calmar_ratio
+    }
+}
+
+/// Wraps another metric and rejects results whose maximum drawdown is deeper than
+/// `max_drawdown` (a positive fraction, e.g. `0.2` for 20%) by scoring them
+/// `f64::NEG_INFINITY`
+pub struct ConstrainedMetric<M> {
+    pub inner: M,
+    pub max_drawdown: f64,
+}
+
+impl<M> ConstrainedMetric<M> {
+    pub fn new(inner: M, max_drawdown: f64) -> Self {
+        Self {
+            inner,
+            max_drawdown,
+        }
+    }
+}
+
+impl<M: OptimizationMetric> OptimizationMetric for ConstrainedMetric<M> {
+    fn calculate(&self, results: &BacktestResults) -> f64 {
+        if results.max_drawdown.abs() > self.max_drawdown {
+            f64::NEG_INFINITY
+        } else {
+            self.inner.calculate(results)
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OptimizationResult {
     pub parameters: HashMap<String, f64>,
@@ -52,7 +101,7 @@ impl GridSearchOptimizer {
 
     pub fn optimize<S, F, M>(
         &self,
-        data: &Vec<OHLCV>,
+        data: &[OHLCV],
         config: &BacktestConfig,
         strategy_factory: F,
         parameter_ranges: HashMap<String, Vec<f64>>,
@@ -66,33 +115,14 @@ impl GridSearchOptimizer {
         // Generate all parameter combinations
         let combinations = self.generate_combinations(&parameter_ranges);
 
-        // Run backtests in parallel
-        let results: Vec<_> = combinations
-            .into_par_iter()
-            .map(|params| {
-                let strategy = strategy_factory(&params);
-                let mut backtest = Backtest::new(data, config.clone());
-
-                match backtest.run(strategy) {
-                    Ok(results) => {
-                        let metric_value = metric.calculate(&results);
-                        Some(OptimizationResult {
-                            parameters: params,
-                            metric_value,
-                            results,
-                        })
-                    }
-                    Err(_) => None,
-                }
-            })
-            .filter_map(|x| x)
-            .collect();
-
-        // Find best result
-        results
-            .into_iter()
-            .max_by(|a, b| a.metric_value.partial_cmp(&b.metric_value).unwrap())
-            .ok_or_else(|| "No valid optimization results found".into())
+        let results = evaluate_combinations(
+            data,
+            config,
+            &strategy_factory,
+            combinations,
+            &metric,
+        );
+        best_result(results)
     }
 
     fn generate_combinations(
@@ -124,3 +154,55 @@ impl Default for GridSearchOptimizer {
         Self::new()
     }
 }
+
+/// Backtest every parameter combination in parallel, dropping the ones that fail
+fn evaluate_combinations<S, F, M>(
+    data: &[OHLCV],
+    config: &BacktestConfig,
+    strategy_factory: &F,
+    combinations: Vec<HashMap<String, f64>>,
+    metric: &M,
+) -> Vec<OptimizationResult>
+where
+    S: Strategy + Send,
+    F: Fn(&HashMap<String, f64>) -> S + Send + Sync,
+    M: OptimizationMetric + Send + Sync,
+{
+    combinations
+        .into_par_iter()
+        .map(|params| {
+            let strategy = strategy_factory(&params);
+            let backtest = Backtest::new(data, config.clone());
+
+            match backtest.run(strategy) {
+                Ok(results) => {
+                    let metric_value = metric.calculate(&results);
+                    Some(OptimizationResult {
+                        parameters: params,
+                        metric_value,
+                        results,
+                    })
+                }
+                Err(_) => None,
+            }
+        })
+        .filter_map(|x| x)
+        .collect()
+}
+
+/// Result with the highest metric value; NaN metrics rank below every number
+fn best_result(results: Vec<OptimizationResult>) -> crate::Result<OptimizationResult> {
+    results
+        .into_iter()
+        .max_by(|a, b| compare_metrics(a.metric_value, b.metric_value))
+        .ok_or_else(|| "No valid optimization results found".into())
+}
+
+fn compare_metrics(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.total_cmp(&b),
+    }
+}
@@ -0,0 +1,111 @@
+use super::{best_result, evaluate_combinations, OptimizationMetric, OptimizationResult};
+use crate::backtest::BacktestConfig;
+use crate::strategy::Strategy;
+use crate::types::OHLCV;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+/// Values a parameter can take during a random search
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterSpace {
+    /// Pick uniformly from a list of values
+    Values(Vec<f64>),
+    /// Pick uniformly from `min, min + step, ..` up to `max`, or from the continuous
+    /// interval `[min, max]` when `step` is not positive
+    Range { min: f64, max: f64, step: f64 },
+}
+
+impl ParameterSpace {
+    fn sample(&self, rng: &mut StdRng) -> Option<f64> {
+        match *self {
+            ParameterSpace::Values(ref values) if values.is_empty() => None,
+            ParameterSpace::Values(ref values) => Some(values[rng.gen_range(0..values.len())]),
+            ParameterSpace::Range { min, max, .. } if max < min => None,
+            ParameterSpace::Range { min, max, step } if step > 0.0 => {
+                let steps = ((max - min) / step).floor() as u64;
+                Some(min + rng.gen_range(0..=steps) as f64 * step)
+            }
+            ParameterSpace::Range { min, max, .. } => Some(rng.gen_range(min..=max)),
+        }
+    }
+}
+
+impl From<Vec<f64>> for ParameterSpace {
+    fn from(values: Vec<f64>) -> Self {
+        ParameterSpace::Values(values)
+    }
+}
+
+/// Backtests `samples` randomly drawn parameter combinations instead of the full grid
+pub struct RandomSearchOptimizer {
+    pub samples: usize,
+    pub seed: u64,
+}
+
+impl RandomSearchOptimizer {
+    pub fn new(samples: usize) -> Self {
+        Self { samples, seed: 0 }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Best sampled combination according to `metric`
+    pub fn optimize<S, F, M>(
+        &self,
+        data: &[OHLCV],
+        config: &BacktestConfig,
+        strategy_factory: F,
+        parameter_spaces: HashMap<String, ParameterSpace>,
+        metric: M,
+    ) -> crate::Result<OptimizationResult>
+    where
+        S: Strategy + Send,
+        F: Fn(&HashMap<String, f64>) -> S + Send + Sync,
+        M: OptimizationMetric + Send + Sync,
+    {
+        best_result(self.evaluate_all(data, config, strategy_factory, parameter_spaces, metric))
+    }
+
+    /// Every sampled combination that backtested successfully, in sampling order,
+    /// for inspecting the parameter landscape
+    pub fn evaluate_all<S, F, M>(
+        &self,
+        data: &[OHLCV],
+        config: &BacktestConfig,
+        strategy_factory: F,
+        parameter_spaces: HashMap<String, ParameterSpace>,
+        metric: M,
+    ) -> Vec<OptimizationResult>
+    where
+        S: Strategy + Send,
+        F: Fn(&HashMap<String, f64>) -> S + Send + Sync,
+        M: OptimizationMetric + Send + Sync,
+    {
+        let combinations = self.sample_combinations(&parameter_spaces);
+        evaluate_combinations(data, config, &strategy_factory, combinations, &metric)
+    }
+
+    fn sample_combinations(
+        &self,
+        spaces: &HashMap<String, ParameterSpace>,
+    ) -> Vec<HashMap<String, f64>> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        // Sort the names so a seed gives the same draws regardless of map order
+        let mut names: Vec<&String> = spaces.keys().collect();
+        names.sort();
+
+        (0..self.samples)
+            .filter_map(|_| {
+                names
+                    .iter()
+                    .map(|&name| Some((name.clone(), spaces[name].sample(&mut rng)?)))
+                    .collect()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,130 @@
+use super::{GridSearchOptimizer, OptimizationMetric};
+use crate::backtest::{Backtest, BacktestConfig, BacktestResults};
+use crate::strategy::Strategy;
+use crate::types::OHLCV;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// One in-sample optimization followed by its out-of-sample evaluation
+#[derive(Debug, Clone)]
+pub struct WalkForwardSegment {
+    /// Bar indices the parameters were optimized on
+    pub in_sample: Range<usize>,
+    /// Bar indices the chosen parameters were evaluated on
+    pub out_of_sample: Range<usize>,
+    pub parameters: HashMap<String, f64>,
+    /// Metric value of the chosen parameters on the in-sample bars
+    pub in_sample_metric: f64,
+    /// Backtest of the chosen parameters on the out-of-sample bars
+    pub results: BacktestResults,
+}
+
+/// Rolling optimization: optimize on `window` bars, trade the next `step` bars with
+/// the winning parameters, then roll both forward by `step` bars.
+///
+/// Each out-of-sample backtest starts flat with `initial_cash` and only sees its own
+/// bars, so indicators warm up again at the start of every segment.
+pub struct WalkForwardOptimizer {
+    pub window: usize,
+    pub step: usize,
+    pub grid: GridSearchOptimizer,
+}
+
+impl WalkForwardOptimizer {
+    pub fn new(window: usize, step: usize) -> Self {
+        Self {
+            window,
+            step,
+            grid: GridSearchOptimizer::new(),
+        }
+    }
+
+    pub fn with_grid(mut self, grid: GridSearchOptimizer) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    pub fn optimize<S, F, M>(
+        &self,
+        data: &[OHLCV],
+        config: &BacktestConfig,
+        strategy_factory: F,
+        parameter_ranges: HashMap<String, Vec<f64>>,
+        metric: M,
+    ) -> crate::Result<Vec<WalkForwardSegment>>
+    where
+        S: Strategy + Send,
+        F: Fn(&HashMap<String, f64>) -> S + Send + Sync,
+        M: OptimizationMetric + Send + Sync,
+    {
+        if self.window == 0 || self.step == 0 {
+            return Err("Walk-forward window and step must be positive".into());
+        }
+
+        let mut segments = Vec::new();
+        let mut start = 0;
+        while start + self.window < data.len() {
+            let in_sample = start..start + self.window;
+            let out_of_sample = in_sample.end..(in_sample.end + self.step).min(data.len());
+
+            let best = self.grid.optimize(
+                &data[in_sample.clone()],
+                config,
+                &strategy_factory,
+                parameter_ranges.clone(),
+                &metric,
+            )?;
+
+            let strategy = strategy_factory(&best.parameters);
+            let backtest = Backtest::new(&data[out_of_sample.clone()], config.clone());
+            let results = backtest.run(strategy)?;
+
+            segments.push(WalkForwardSegment {
+                in_sample,
+                out_of_sample,
+                parameters: best.parameters,
+                in_sample_metric: best.metric_value,
+                results,
+            });
+            start += self.step;
+        }
+
+        if segments.is_empty() {
+            return Err(format!(
+                "Walk-forward needs more than {} bars, got {}",
+                self.window,
+                data.len()
+            )
+            .into());
+        }
+
+        Ok(segments)
+    }
+
+    /// Chain the out-of-sample equity curves into one, compounding each segment's
+    /// returns onto the equity the previous segment finished with
+    pub fn stitch_equity(
+        segments: &[WalkForwardSegment],
+        initial_cash: f64,
+    ) -> Vec<(DateTime<Utc>, f64)> {
+        let mut curve = Vec::new();
+        let mut equity = initial_cash;
+
+        for segment in segments {
+            let scale = equity / initial_cash;
+            curve.extend(
+                segment
+                    .results
+                    .equity_curve
+                    .iter()
+                    .map(|&(timestamp, value)| (timestamp, value * scale)),
+            );
+            if let Some(&(_, last)) = curve.last() {
+                equity = last;
+            }
+        }
+
+        curve
+    }
+}
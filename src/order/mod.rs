@@ -12,6 +12,18 @@ pub enum OrderType {
     StopLimit,
 }
 
+impl OrderType {
+    /// Order type implied by which trigger prices are set
+    pub fn for_prices(limit: Option<f64>, stop: Option<f64>) -> Self {
+        match (limit, stop) {
+            (Some(_), Some(_)) => OrderType::StopLimit,
+            (Some(_), None) => OrderType::Limit,
+            (None, Some(_)) => OrderType::Stop,
+            (None, None) => OrderType::Market,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderSide {
     Buy,
@@ -26,11 +38,25 @@ pub enum OrderStatus {
     PartiallyFilled,
 }
 
+/// What an order's `size` counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SizeUnit {
+    /// Units of the instrument, fractional ones included
+    #[default]
+    Units,
+    /// Fraction of current equity, resolved into units at fill time
+    EquityFraction,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Order {
     pub side: OrderSide,
     pub order_type: OrderType,
+    /// Amount to trade, counted in `size_unit`
     pub size: f64,
+    /// Whether `size` is a number of units or a fraction of equity
+    #[serde(default)]
+    pub size_unit: SizeUnit,
     pub limit: Option<f64>,
     pub stop: Option<f64>,
     pub sl: Option<f64>,
@@ -39,9 +65,24 @@ pub struct Order {
     pub timestamp: DateTime<Utc>,
     pub status: OrderStatus,
     pub filled_size: f64,
+    /// Number of consecutive bars the same-side order must be submitted before it fills
+    pub confirm_bars: usize,
+    /// Instrument the order targets in multi-asset backtests
+    pub symbol: Option<String>,
+    /// Close open positions instead of trading `size` units; `size` is then the
+    /// fraction of each position to close, 1.0 for all of it
+    pub flatten: bool,
+    /// Position a `flatten` order closes, every open position when `None`
+    #[serde(default)]
+    pub position_id: Option<usize>,
+    /// Trailing stop distance as a fraction of the best price since entry
+    pub trail_percent: Option<f64>,
+    /// Trailing stop distance in price units from the best price since entry
+    pub trail_amount: Option<f64>,
 }
 
 impl Order {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         side: OrderSide,
         order_type: OrderType,
@@ -56,6 +97,7 @@ impl Order {
             side,
             order_type,
             size,
+            size_unit: SizeUnit::Units,
             limit,
             stop,
             sl,
@@ -64,9 +106,79 @@ impl Order {
             timestamp: Utc::now(),
             status: OrderStatus::Pending,
             filled_size: 0.0,
+            confirm_bars: 0,
+            symbol: None,
+            flatten: false,
+            position_id: None,
+            trail_percent: None,
+            trail_amount: None,
         }
     }
 
+    /// Market order that closes whatever position is open when it is processed
+    pub fn flatten() -> Self {
+        Self::close_portion(1.0)
+    }
+
+    /// Market order that closes `fraction` (0.0 to 1.0) of whatever position is open
+    /// when it is processed
+    pub fn close_portion(fraction: f64) -> Self {
+        let mut order = Self::new(
+            OrderSide::Sell,
+            OrderType::Market,
+            fraction.clamp(0.0, 1.0),
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        order.flatten = true;
+        order
+    }
+
+    /// Market order that closes `fraction` (0.0 to 1.0) of the open position with
+    /// `Position::id` `id` and leaves the others alone, so one leg of a hedged book can
+    /// be exited. Does nothing once that position is closed.
+    pub fn close_position(id: usize, fraction: f64) -> Self {
+        let mut order = Self::close_portion(fraction);
+        order.position_id = Some(id);
+        order
+    }
+
+    /// Trade `fraction` of current equity (0.5 for half) instead of a number of units.
+    /// The fraction is resolved into units at fill time and covers the fill's
+    /// commission and slippage.
+    pub fn with_equity_fraction(mut self, fraction: f64) -> Self {
+        self.size = fraction;
+        self.size_unit = SizeUnit::EquityFraction;
+        self
+    }
+
+    /// Target a specific instrument
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    /// Trail a stop `percent` (e.g. 0.05 for 5%) behind the best price reached
+    pub fn with_trail_percent(mut self, percent: f64) -> Self {
+        self.trail_percent = Some(percent);
+        self
+    }
+
+    /// Trail a stop `amount` price units behind the best price reached
+    pub fn with_trail_amount(mut self, amount: f64) -> Self {
+        self.trail_amount = Some(amount);
+        self
+    }
+
+    /// Require the signal to be re-submitted on `bars` consecutive bars before filling
+    pub fn with_confirm_bars(mut self, bars: usize) -> Self {
+        self.confirm_bars = bars;
+        self
+    }
+
     pub fn is_long(&self) -> bool {
         matches!(self.side, OrderSide::Buy) && self.size > 0.0
     }
@@ -1,13 +1,21 @@
-use crate::backtest::BacktestResults;
+use crate::backtest::{BacktestResults, Calculations};
 use crate::types::OHLCV;
 use crate::Trade;
 use chrono::{DateTime, Utc};
 use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
 use plotters::prelude::*;
+use std::path::Path;
+
+/// Series longer than this are drawn as a close line even in candlestick mode, since
+/// the candles would be narrower than a pixel
+const MAX_CANDLES: usize = 500;
 
 pub struct PlotConfig {
     pub width: u32,
     pub height: u32,
+    /// Draw OHLC candlesticks instead of a close-price line, for up to 500 bars
+    pub candlestick: bool,
     pub show_trades: bool,
     pub show_equity_curve: bool,
     pub show_drawdown: bool,
@@ -18,6 +26,7 @@ impl Default for PlotConfig {
         Self {
             width: 1024,
             height: 768,
+            candlestick: false,
             show_trades: true,
             show_equity_curve: true,
             show_drawdown: false,
@@ -28,64 +37,210 @@ impl Default for PlotConfig {
 pub struct BacktestPlotter;
 
 impl BacktestPlotter {
+    /// Draw the price chart to `output_path`, plus the equity curve next to it when
+    /// `show_equity_curve` is set.
+    ///
+    /// A `.svg` path renders vector SVG; any other extension renders a PNG bitmap. The
+    /// equity chart goes to the same path with `_equity` appended to the file stem.
     pub fn plot(
         data: &[OHLCV],
         results: &BacktestResults,
         output_path: &str,
         config: PlotConfig,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let latest_price = data.last().unwrap_or(&OHLCV::default()).close;
-        let root =
-            BitMapBackend::new(output_path, (config.width, config.height)).into_drawing_area();
+        let size = (config.width, config.height);
+        if Self::is_svg(output_path) {
+            let root = SVGBackend::new(output_path, size).into_drawing_area();
+            Self::draw_price_chart(&root, data, &results.trades, &[], &config)?;
+        } else {
+            let root = BitMapBackend::new(output_path, size).into_drawing_area();
+            Self::draw_price_chart(&root, data, &results.trades, &[], &config)?;
+        }
+
+        // Plot equity curve if enabled
+        if config.show_equity_curve {
+            Self::plot_equity_curve(
+                &results.equity_curve,
+                results.initial_equity,
+                &Self::equity_path(output_path),
+                &config,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Draw an equity curve on its own, e.g. one stitched together outside a backtest.
+    /// The first point's equity is drawn as the baseline.
+    pub fn plot_equity(
+        curve: &[(DateTime<Utc>, f64)],
+        output_path: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let baseline = curve.first().map_or(0.0, |&(_, equity)| equity);
+        Self::plot_equity_curve(curve, baseline, output_path, &PlotConfig::default())
+    }
+
+    /// Draw the close price with indicator lines overlaid, without any backtest results.
+    ///
+    /// Each indicator is a name and one value per bar of `data`; `None` leaves a gap,
+    /// e.g. during an indicator's warm-up.
+    pub fn plot_with_indicators(
+        data: &[OHLCV],
+        indicators: &[(String, Vec<Option<f64>>)],
+        output_path: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let config = PlotConfig {
+            show_trades: false,
+            ..PlotConfig::default()
+        };
+        let size = (config.width, config.height);
+        if Self::is_svg(output_path) {
+            let root = SVGBackend::new(output_path, size).into_drawing_area();
+            Self::draw_price_chart(&root, data, &[], indicators, &config)
+        } else {
+            let root = BitMapBackend::new(output_path, size).into_drawing_area();
+            Self::draw_price_chart(&root, data, &[], indicators, &config)
+        }
+    }
+
+    fn is_svg(output_path: &str) -> bool {
+        Path::new(output_path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+    }
+
+    /// `output_path` with `_equity` appended to the file stem, e.g. `chart_equity.png`
+    fn equity_path(output_path: &str) -> String {
+        let path = Path::new(output_path);
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("chart");
+        let file_name = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) => format!("{stem}_equity.{ext}"),
+            None => format!("{stem}_equity"),
+        };
+        path.with_file_name(file_name)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn draw_price_chart<DB: DrawingBackend>(
+        root: &DrawingArea<DB, Shift>,
+        data: &[OHLCV],
+        trades: &[Trade],
+        indicators: &[(String, Vec<Option<f64>>)],
+        config: &PlotConfig,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let (Some(first), Some(last)) = (data.first(), data.last()) else {
+            return Err("No price data to plot".into());
+        };
+        let latest_price = last.close;
         root.fill(&WHITE)?;
 
-        let price_range = Self::find_price_range(data);
-        let mut chart = ChartBuilder::on(&root)
+        let price_range = Self::find_price_range(data, indicators);
+        let mut chart = ChartBuilder::on(root)
             .caption("Backtest Results", ("sans-serif", 40))
             .margin(5)
             .x_label_area_size(40)
             .y_label_area_size(50)
-            .build_cartesian_2d(
-                data.first().unwrap().timestamp..data.last().unwrap().timestamp,
-                price_range,
-            )?;
+            .build_cartesian_2d(first.timestamp..last.timestamp, price_range)?;
 
         chart.configure_mesh().draw()?;
 
         // Plot price data
-        chart
-            .draw_series(LineSeries::new(
-                data.iter().map(|bar| (bar.timestamp, bar.close)),
-                &BLACK,
-            ))?
-            .label("Price")
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], &BLACK));
+        if config.candlestick && data.len() <= MAX_CANDLES {
+            let candle_width = (config.width as usize * 7 / 10 / data.len().max(1)).max(1) as u32;
+            chart
+                .draw_series(data.iter().map(|bar| {
+                    CandleStick::new(
+                        bar.timestamp,
+                        bar.open,
+                        bar.high,
+                        bar.low,
+                        bar.close,
+                        GREEN.filled(),
+                        RED.filled(),
+                        candle_width,
+                    )
+                }))?
+                .label("Price")
+                .legend(|(x, y)| Rectangle::new([(x, y - 4), (x + 10, y + 4)], GREEN.filled()));
+        } else {
+            chart
+                .draw_series(LineSeries::new(
+                    data.iter().map(|bar| (bar.timestamp, bar.close)),
+                    &BLACK,
+                ))?
+                .label("Price")
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], BLACK));
+        }
+
+        // Overlay indicators, breaking each line where it has no value
+        for (index, (name, values)) in indicators.iter().enumerate() {
+            let color = Palette99::pick(index).to_rgba();
+            let segments = Self::indicator_segments(data, values);
+            let mut labelled = false;
+            for segment in segments {
+                let series = chart.draw_series(LineSeries::new(segment, color.stroke_width(2)))?;
+                if !labelled {
+                    series
+                        .label(name.as_str())
+                        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], color));
+                    labelled = true;
+                }
+            }
+        }
 
         // Plot trades if enabled
         if config.show_trades {
-            Self::plot_trades(&mut chart, &results.trades, latest_price)?;
+            Self::plot_trades(&mut chart, trades, latest_price)?;
         }
 
         chart.configure_series_labels().draw()?;
         root.present()?;
 
-        // Plot equity curve if enabled
-        if config.show_equity_curve {
-            Self::plot_equity_curve(
-                results,
-                output_path.replace(".png", "_equity.png").as_str(),
-                &config,
-            )?;
-        }
-
         Ok(())
     }
 
-    fn find_price_range(data: &[OHLCV]) -> RangedCoordf64 {
-        let min_price = data.iter().map(|bar| bar.low).fold(f64::INFINITY, f64::min);
+    /// Contiguous runs of an indicator's values, paired with the bar timestamps
+    fn indicator_segments(
+        data: &[OHLCV],
+        values: &[Option<f64>],
+    ) -> Vec<Vec<(DateTime<Utc>, f64)>> {
+        let mut segments = vec![Vec::new()];
+        for (bar, value) in data.iter().zip(values) {
+            match value {
+                Some(value) if value.is_finite() => {
+                    segments.last_mut().unwrap().push((bar.timestamp, *value))
+                }
+                _ if segments.last().is_some_and(|s| !s.is_empty()) => segments.push(Vec::new()),
+                _ => {}
+            }
+        }
+        segments.retain(|segment| !segment.is_empty());
+        segments
+    }
+
+    fn find_price_range(
+        data: &[OHLCV],
+        indicators: &[(String, Vec<Option<f64>>)],
+    ) -> RangedCoordf64 {
+        let indicator_values = || {
+            indicators
+                .iter()
+                .flat_map(|(_, values)| values.iter().flatten().copied())
+                .filter(|value| value.is_finite())
+        };
+        let min_price = data
+            .iter()
+            .map(|bar| bar.low)
+            .chain(indicator_values())
+            .fold(f64::INFINITY, f64::min);
         let max_price = data
             .iter()
             .map(|bar| bar.high)
+            .chain(indicator_values())
             .fold(f64::NEG_INFINITY, f64::max);
         let padding = (max_price - min_price) * 0.1;
         ((min_price - padding)..(max_price + padding)).into()
@@ -103,79 +258,201 @@ impl BacktestPlotter {
     where
         DB::ErrorType: 'static,
     {
-        // Plot buy points (green)
+        let exit_point = |trade: &Trade| (trade.get_exit_time(), trade.get_exit_price(last_price));
+        let outcome_color = |trade: &Trade| {
+            if trade.pl() > 0.0 {
+                GREEN
+            } else {
+                RED
+            }
+        };
+
+        // Holding periods: entry joined to exit
+        chart.draw_series(trades.iter().map(|trade| {
+            PathElement::new(
+                vec![(trade.entry_time, trade.entry_price), exit_point(trade)],
+                outcome_color(trade).mix(0.6).stroke_width(1),
+            )
+        }))?;
+
+        // Long entries point up, short entries point down
         chart
-            .draw_series(trades.iter().map(|trade| {
-                Circle::new((trade.entry_time, trade.entry_price), 3, GREEN.filled())
+            .draw_series(trades.iter().filter(|trade| trade.is_long()).map(|trade| {
+                TriangleMarker::new((trade.entry_time, trade.entry_price), 6, BLUE.filled())
             }))?
-            .label("Buy")
-            .legend(|(x, y)| Circle::new((x + 5, y), 3, GREEN.filled()));
+            .label("Long entry")
+            .legend(|(x, y)| TriangleMarker::new((x + 5, y), 6, BLUE.filled()));
 
-        // Plot sell points (red)
         chart
-            .draw_series(trades.iter().map(|trade| {
-                Circle::new(
-                    (trade.get_exit_time(), trade.get_exit_price(last_price)),
-                    3,
-                    RED.filled(),
-                )
+            .draw_series(trades.iter().filter(|trade| trade.is_short()).map(|trade| {
+                EmptyElement::at((trade.entry_time, trade.entry_price))
+                    + Polygon::new(vec![(-5, -4), (5, -4), (0, 5)], MAGENTA.filled())
             }))?
-            .label("Sell")
-            .legend(|(x, y)| Circle::new((x + 5, y), 3, RED.filled()));
+            .label("Short entry")
+            .legend(|(x, y)| {
+                Polygon::new(
+                    vec![(x, y - 4), (x + 10, y - 4), (x + 5, y + 5)],
+                    MAGENTA.filled(),
+                )
+            });
+
+        // Exits colored by the trade's outcome
+        chart
+            .draw_series(
+                trades
+                    .iter()
+                    .filter(|trade| trade.pl() > 0.0)
+                    .map(|trade| Circle::new(exit_point(trade), 4, outcome_color(trade).filled())),
+            )?
+            .label("Winning exit")
+            .legend(|(x, y)| Circle::new((x + 5, y), 4, GREEN.filled()));
+
+        chart
+            .draw_series(
+                trades
+                    .iter()
+                    .filter(|trade| trade.pl() <= 0.0)
+                    .map(|trade| Circle::new(exit_point(trade), 4, outcome_color(trade).filled())),
+            )?
+            .label("Losing exit")
+            .legend(|(x, y)| Circle::new((x + 5, y), 4, RED.filled()));
 
         Ok(())
     }
 
+    /// Draw the equity curve, with the underwater drawdown curve below it when
+    /// `show_drawdown` is set
     fn plot_equity_curve(
-        results: &BacktestResults,
+        curve: &[(DateTime<Utc>, f64)],
+        baseline: f64,
         output_path: &str,
         config: &PlotConfig,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let root =
-            BitMapBackend::new(output_path, (config.width, config.height)).into_drawing_area();
-        root.fill(&WHITE)?;
+        if curve.is_empty() {
+            return Err("No equity curve to plot".into());
+        }
+
+        let size = (config.width, config.height);
+        if Self::is_svg(output_path) {
+            let root = SVGBackend::new(output_path, size).into_drawing_area();
+            Self::draw_equity_page(&root, curve, baseline, config)
+        } else {
+            let root = BitMapBackend::new(output_path, size).into_drawing_area();
+            Self::draw_equity_page(&root, curve, baseline, config)
+        }
+    }
 
-        // Calculate equity curve from trades
-        let mut equity_points = vec![(results.start_date, 10000.0)]; // Initial cash
-        let mut current_equity = 10000.0;
+    fn draw_equity_page<DB: DrawingBackend>(
+        root: &DrawingArea<DB, Shift>,
+        curve: &[(DateTime<Utc>, f64)],
+        baseline: f64,
+        config: &PlotConfig,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
 
-        for trade in &results.trades {
-            current_equity += trade.pl();
-            equity_points.push((trade.get_exit_time(), current_equity));
+        if config.show_drawdown {
+            let (upper, lower) = root.split_vertically(config.height * 2 / 3);
+            Self::draw_equity(&upper, curve, baseline)?;
+            Self::draw_drawdown(&lower, curve)?;
+        } else {
+            Self::draw_equity(root, curve, baseline)?;
         }
+        root.present()?;
+
+        Ok(())
+    }
+
+    /// Equity line with a flat `baseline`, the starting cash, for reference
+    fn draw_equity<DB: DrawingBackend>(
+        area: &DrawingArea<DB, Shift>,
+        equity_points: &[(DateTime<Utc>, f64)],
+        baseline: f64,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let (start, end) = match (equity_points.first(), equity_points.last()) {
+            (Some(first), Some(last)) => (first.0, last.0),
+            _ => return Err("No equity curve to plot".into()),
+        };
 
+        // Keep the starting cash in view so gains and losses read against it
         let min_equity = equity_points
             .iter()
             .map(|(_, e)| *e)
-            .fold(f64::INFINITY, f64::min);
+            .fold(baseline, f64::min);
         let max_equity = equity_points
             .iter()
             .map(|(_, e)| *e)
-            .fold(f64::NEG_INFINITY, f64::max);
-        let padding = (max_equity - min_equity) * 0.1;
-
-        let equity_range: RangedCoordf64 = if min_equity == max_equity {
-            ((min_equity - 1000.0)..(max_equity + 1000.0)).into()
+            .fold(baseline, f64::max);
+        let padding = if max_equity > min_equity {
+            (max_equity - min_equity) * 0.1
         } else {
-            ((min_equity - padding)..(max_equity + padding)).into()
+            // Flat curve: pad relative to its level so the line sits mid-chart
+            (max_equity.abs() * 0.01).max(1.0)
         };
+        let equity_range: RangedCoordf64 = ((min_equity - padding)..(max_equity + padding)).into();
 
-        let mut chart = ChartBuilder::on(&root)
+        let mut chart = ChartBuilder::on(area)
             .caption("Equity Curve", ("sans-serif", 40))
             .margin(5)
             .x_label_area_size(40)
             .y_label_area_size(50)
-            .build_cartesian_2d(results.start_date..results.end_date, equity_range)?;
+            .build_cartesian_2d(start..end, equity_range)?;
 
         chart.configure_mesh().draw()?;
 
         chart
-            .draw_series(LineSeries::new(equity_points, &BLUE))?
+            .draw_series(LineSeries::new(equity_points.iter().copied(), &BLUE))?
             .label("Equity")
-            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], &BLUE));
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], BLUE));
+
+        chart
+            .draw_series(LineSeries::new(
+                [(start, baseline), (end, baseline)],
+                &BLACK.mix(0.4),
+            ))?
+            .label("Initial cash")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], BLACK.mix(0.4)));
 
         chart.configure_series_labels().draw()?;
-        root.present()?;
+
+        Ok(())
+    }
+
+    /// Underwater curve: drawdown from the running peak as a filled area below zero
+    fn draw_drawdown<DB: DrawingBackend>(
+        area: &DrawingArea<DB, Shift>,
+        curve: &[(DateTime<Utc>, f64)],
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        let drawdowns = Calculations::drawdown_series(curve);
+        let (start, end) = match (drawdowns.first(), drawdowns.last()) {
+            (Some(first), Some(last)) => (first.0, last.0),
+            _ => return Err("No drawdown to plot".into()),
+        };
+
+        let deepest = drawdowns.iter().map(|(_, dd)| *dd).fold(0.0, f64::min);
+        let floor = if deepest < 0.0 { deepest * 1.1 } else { -0.01 };
+
+        let mut chart = ChartBuilder::on(area)
+            .margin(5)
+            .x_label_area_size(40)
+            .y_label_area_size(50)
+            .build_cartesian_2d(start..end, floor..0.0)?;
+
+        chart
+            .configure_mesh()
+            .y_desc("Drawdown")
+            .y_label_formatter(&|v| format!("{:.1}%", v * 100.0))
+            .draw()?;
+
+        chart.draw_series(AreaSeries::new(drawdowns, 0.0, RED.mix(0.3)).border_style(RED))?;
 
         Ok(())
     }
@@ -0,0 +1,626 @@
+use super::PortfolioSnapshot;
+use crate::backtest::accounting::{self, BuyingPower, Fill};
+use crate::backtest::{BacktestConfig, Calculations, PartialFills};
+use crate::order::{Order, OrderSide, OrderStatus, OrderType, SizeUnit};
+use crate::position::Position;
+use crate::trade::Trade;
+use crate::types::OHLCV;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// A strategy that trades several symbols from one cash pool
+pub trait PortfolioStrategy {
+    /// Called once with every symbol's bars before the backtest begins
+    fn init(&mut self, _data: &HashMap<String, Vec<OHLCV>>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called at each timestamp with the bars of the symbols that traded at it and the
+    /// account with those symbols marked to the price market orders fill at: the
+    /// bar's open when the config fills on the open, its close otherwise.
+    ///
+    /// Every order must name its symbol with `Order::with_symbol`; orders for a symbol
+    /// without a bar at this timestamp are ignored.
+    fn next(
+        &mut self,
+        bars: &HashMap<String, OHLCV>,
+        portfolio: &PortfolioSnapshot,
+        index: usize,
+    ) -> Result<Vec<Order>>;
+}
+
+/// Results of a `PortfolioBacktest`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioResults {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub initial_equity: f64,
+    pub equity_final: f64,
+    pub return_pct: f64,
+    pub return_ann: f64,
+    pub volatility_ann: f64,
+    pub sharpe_ratio: f64,
+    pub max_drawdown: f64,
+    /// Closed trades per symbol
+    pub trades: HashMap<String, Vec<Trade>>,
+    /// Account at the last timestamp, with open positions marked to their last price
+    pub final_portfolio: PortfolioSnapshot,
+    /// Orders the buying power rejected or cut short and that aren't resting, with the
+    /// units they did fill
+    pub unfilled_orders: Vec<Order>,
+    pub equity_curve: Vec<(DateTime<Utc>, f64)>,
+}
+
+impl PortfolioResults {
+    /// Number of closed trades across all symbols
+    pub fn trade_count(&self) -> usize {
+        self.trades.values().map(Vec::len).sum()
+    }
+}
+
+/// Backtest over several symbols sharing one cash pool.
+///
+/// Bars are aligned by timestamp: the strategy is called once for every timestamp any
+/// symbol has a bar at, with the bars of the symbols that traded then. Market orders
+/// fill at the symbol's close, or at the open of its next bar when the config defers
+/// market orders, and opposite fills net against the symbol's open position. The
+/// config's commission, slippage, margin, partial fills, fractional units, lot
+/// matching, risk-free rate and return basis settings apply.
+pub struct PortfolioBacktest {
+    data: HashMap<String, Vec<OHLCV>>,
+    config: BacktestConfig,
+}
+
+impl PortfolioBacktest {
+    /// Each symbol's bars must be in timestamp order
+    pub fn new(data: HashMap<String, Vec<OHLCV>>, config: BacktestConfig) -> Self {
+        Self { data, config }
+    }
+
+    pub fn run<S: PortfolioStrategy>(&self, mut strategy: S) -> Result<PortfolioResults> {
+        strategy.init(&self.data)?;
+
+        let timestamps: BTreeSet<DateTime<Utc>> = self
+            .data
+            .values()
+            .flat_map(|bars| bars.iter().map(|bar| bar.timestamp))
+            .collect();
+        let mut cursors: HashMap<&str, usize> = self
+            .data
+            .keys()
+            .map(|symbol| (symbol.as_str(), 0))
+            .collect();
+        let mut state = PortfolioState::new(&self.config);
+        let mut deferred: Vec<Order> = Vec::new();
+
+        for (index, &timestamp) in timestamps.iter().enumerate() {
+            let mut bars = HashMap::new();
+            for (symbol, cursor) in cursors.iter_mut() {
+                if let Some(bar) = self.data[*symbol].get(*cursor) {
+                    if bar.timestamp == timestamp {
+                        bars.insert(symbol.to_string(), bar.clone());
+                        *cursor += 1;
+                    }
+                }
+            }
+
+            // Market orders deferred to the next open fill on their symbol's next bar
+            for order in std::mem::take(&mut deferred) {
+                let symbol = order.symbol.as_deref().unwrap_or_default();
+                match bars.get(symbol) {
+                    Some(bar) => {
+                        let mut order = order;
+                        state.fill_order(&mut order, bar, bar.open, index)?;
+                        state.rest_remainder(order, &mut deferred);
+                    }
+                    None => deferred.push(order),
+                }
+            }
+
+            // Show the strategy the prices its market orders fill at, so it sizes them
+            // against this bar rather than the last one
+            let fill_price = |bar: &OHLCV| match self.config.fills_on_open() {
+                true => bar.open,
+                false => bar.close,
+            };
+            for (symbol, bar) in &bars {
+                state.mark(symbol, fill_price(bar));
+            }
+
+            let snapshot = state.snapshot();
+            for mut order in strategy.next(&bars, &snapshot, index)? {
+                let Some(symbol) = order.symbol.clone() else {
+                    return Err("portfolio orders must name a symbol".into());
+                };
+                if order.order_type != OrderType::Market && !order.flatten {
+                    return Err("portfolio backtests only support market orders".into());
+                }
+                if self.config.defers_market_orders() {
+                    deferred.push(order);
+                } else if let Some(bar) = bars.get(&symbol) {
+                    state.fill_order(&mut order, bar, fill_price(bar), index)?;
+                    state.rest_remainder(order, &mut deferred);
+                }
+            }
+
+            for (symbol, bar) in &bars {
+                state.mark(symbol, bar.close);
+            }
+            let equity = state.equity();
+            state.equity_curve.push((timestamp, equity));
+        }
+
+        state.into_results()
+    }
+}
+
+/// Account state while a `PortfolioBacktest` runs
+struct PortfolioState<'a> {
+    config: &'a BacktestConfig,
+    cash: f64,
+    positions: HashMap<String, Position>,
+    prices: HashMap<String, f64>,
+    trades: HashMap<String, Vec<Trade>>,
+    unfilled_orders: Vec<Order>,
+    equity_curve: Vec<(DateTime<Utc>, f64)>,
+}
+
+impl<'a> PortfolioState<'a> {
+    fn new(config: &'a BacktestConfig) -> Self {
+        Self {
+            config,
+            cash: config.initial_cash,
+            positions: HashMap::new(),
+            prices: HashMap::new(),
+            trades: HashMap::new(),
+            unfilled_orders: Vec::new(),
+            equity_curve: Vec::new(),
+        }
+    }
+
+    fn snapshot(&self) -> PortfolioSnapshot {
+        PortfolioSnapshot {
+            cash: self.cash,
+            holdings: self
+                .positions
+                .iter()
+                .map(|(symbol, position)| (symbol.clone(), position.size))
+                .collect(),
+            prices: self.prices.clone(),
+        }
+    }
+
+    fn equity(&self) -> f64 {
+        accounting::equity(self.cash, self.positions.values())
+    }
+
+    fn mark(&mut self, symbol: &str, price: f64) {
+        self.prices.insert(symbol.to_string(), price);
+        if let Some(position) = self.positions.get_mut(symbol) {
+            position.update_price(price);
+        }
+    }
+
+    /// Execute `order` on its symbol at `price`, leaving it `PartiallyFilled` when the
+    /// buying power cut it short
+    fn fill_order(
+        &mut self,
+        order: &mut Order,
+        bar: &OHLCV,
+        price: f64,
+        index: usize,
+    ) -> Result<()> {
+        let symbol = order.symbol.clone().unwrap_or_default();
+        self.mark(&symbol, price);
+
+        if order.flatten {
+            let Some(position) = self.positions.get(&symbol) else {
+                return Ok(());
+            };
+            let size = self
+                .config
+                .tradable_units(position.size.abs() * order.size.clamp(0.0, 1.0));
+            let side = if position.is_long() {
+                OrderSide::Sell
+            } else {
+                OrderSide::Buy
+            };
+            let price =
+                accounting::slipped_price(self.config, Some(&symbol), side, price, size, bar, true);
+            return self.close(&symbol, size, price, bar, index);
+        }
+
+        let size = match order.size_unit {
+            SizeUnit::EquityFraction => accounting::equity_fraction_units(
+                self.config,
+                order,
+                self.equity(),
+                price,
+                bar,
+                true,
+            ),
+            SizeUnit::Units => order.remaining_size(),
+        };
+        let size = self.config.tradable_units(size);
+        if size <= 0.0 {
+            return Ok(());
+        }
+        let price = accounting::slipped_price(
+            self.config,
+            Some(&symbol),
+            order.side,
+            price,
+            size,
+            bar,
+            true,
+        );
+
+        // Net against an opposite position before opening the remainder
+        let mut remaining = size;
+        if let Some(position) = self.positions.get(&symbol) {
+            if position.is_long() != (order.side == OrderSide::Buy) {
+                let close_size = remaining.min(position.size.abs());
+                self.close(&symbol, close_size, price, bar, index)?;
+                remaining -= close_size;
+            }
+        }
+        let unfilled = match remaining > 0.0 {
+            true => self.open(&symbol, order, remaining, price, bar, index),
+            false => 0.0,
+        };
+
+        let fill = Fill {
+            size,
+            price,
+            bar_index: index,
+            timestamp: bar.timestamp,
+        };
+        accounting::settle_order(order, &fill, unfilled);
+        Ok(())
+    }
+
+    /// Rest the unfilled part of an order the buying power cut short, to fill at its
+    /// symbol's next open, when `PartialFills::RestRemainder` is configured; otherwise
+    /// record it as unfilled
+    fn rest_remainder(&mut self, order: Order, resting: &mut Vec<Order>) {
+        if order.status != OrderStatus::PartiallyFilled {
+            return;
+        }
+        match self.config.partial_fills {
+            PartialFills::RestRemainder => resting.push(order),
+            PartialFills::Reject | PartialFills::CancelRemainder => {
+                self.unfilled_orders.push(order)
+            }
+        }
+    }
+
+    /// Open or add to the position in `symbol`, returning the units left unfilled for
+    /// lack of buying power
+    fn open(
+        &mut self,
+        symbol: &str,
+        order: &Order,
+        size: f64,
+        price: f64,
+        bar: &OHLCV,
+        index: usize,
+    ) -> f64 {
+        // Every open position across the symbols plus the fill must fit the buying power
+        let fillable = BuyingPower::of(self.cash, self.positions.values()).fillable_units(
+            self.config,
+            Some(symbol),
+            size,
+            price,
+        );
+        if fillable <= 0.0 {
+            return size;
+        }
+
+        let fill = Fill {
+            size: fillable,
+            price,
+            bar_index: index,
+            timestamp: bar.timestamp,
+        };
+        let position = self
+            .positions
+            .entry(symbol.to_string())
+            .or_insert_with(|| Position::new(0.0, price, bar.timestamp));
+        let commission = accounting::open(position, self.config, order, order.side, &fill);
+        position.update_price(price);
+
+        // Short sale proceeds are not credited; the short's P&L settles on cover
+        if order.side == OrderSide::Buy {
+            self.cash -= fillable * price;
+        }
+        self.cash -= commission;
+        size - fillable
+    }
+
+    fn close(
+        &mut self,
+        symbol: &str,
+        size: f64,
+        price: f64,
+        bar: &OHLCV,
+        index: usize,
+    ) -> Result<()> {
+        let Some(position) = self.positions.get_mut(symbol) else {
+            return Ok(());
+        };
+        if size.min(position.size.abs()) <= 0.0 {
+            return Ok(());
+        }
+
+        let fill = Fill {
+            size,
+            price,
+            bar_index: index,
+            timestamp: bar.timestamp,
+        };
+        let exit = accounting::close(position, self.config, &fill);
+        if position.size == 0.0 {
+            self.positions.remove(symbol);
+        }
+
+        self.trades
+            .entry(symbol.to_string())
+            .or_default()
+            .extend(exit.trades);
+        self.cash += exit.settlement - exit.commission;
+        Ok(())
+    }
+
+    fn into_results(self) -> Result<PortfolioResults> {
+        let (Some(&(start_date, _)), Some(&(end_date, equity_final))) =
+            (self.equity_curve.first(), self.equity_curve.last())
+        else {
+            return Err("No data or equity curve available".into());
+        };
+
+        let initial_equity = self.config.initial_cash;
+        let return_pct = (equity_final - initial_equity) / initial_equity;
+        let years = Calculations::year_fraction(start_date, end_date);
+        let return_ann = if years > 0.0 {
+            (1.0 + return_pct).powf(1.0 / years) - 1.0
+        } else {
+            0.0
+        };
+
+        let return_basis = self.config.return_basis;
+        let periods_per_year = self.config.periods_per_year.unwrap_or_else(|| {
+            let timestamps: Vec<DateTime<Utc>> = self
+                .equity_curve
+                .iter()
+                .map(|&(timestamp, _)| timestamp)
+                .collect();
+            Calculations::infer_periods_per_year_from(&timestamps)
+        });
+        let volatility_ann =
+            Calculations::calculate_volatility(&self.equity_curve, periods_per_year, return_basis);
+        let sharpe_ratio = if volatility_ann > 0.0 {
+            return_basis.excess_return(return_ann, self.config.risk_free_rate) / volatility_ann
+        } else {
+            0.0
+        };
+        let (max_drawdown, ..) = Calculations::calculate_drawdown_metrics(&self.equity_curve);
+
+        Ok(PortfolioResults {
+            start_date,
+            end_date,
+            initial_equity,
+            equity_final,
+            return_pct,
+            return_ann,
+            volatility_ann,
+            sharpe_ratio,
+            max_drawdown,
+            final_portfolio: self.snapshot(),
+            unfilled_orders: self.unfilled_orders,
+            trades: self.trades,
+            equity_curve: self.equity_curve,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::{CommissionModel, PerShare, Percentage};
+    use crate::test_util::flat_bars;
+    use std::sync::Arc;
+
+    /// Rebalances to the weights listed for each bar
+    struct Rebalance(Vec<HashMap<String, f64>>);
+
+    impl PortfolioStrategy for Rebalance {
+        fn next(
+            &mut self,
+            _bars: &HashMap<String, OHLCV>,
+            portfolio: &PortfolioSnapshot,
+            index: usize,
+        ) -> Result<Vec<Order>> {
+            match self.0.get(index) {
+                Some(weights) => portfolio.rebalance_to_weights(weights),
+                None => Ok(vec![]),
+            }
+        }
+    }
+
+    #[test]
+    fn rebalance_deltas_below_one_unit_trade_units() {
+        let weights = |w: f64| HashMap::from([("BTC".to_string(), w)]);
+        let steps = [weights(0.5), weights(0.6), weights(0.55)];
+        let held_after = |rebalances: usize| {
+            let data = HashMap::from([("BTC".to_string(), flat_bars(&[30_000.0; 4]))]);
+            let config = BacktestConfig {
+                initial_cash: 100_000.0,
+                ..Default::default()
+            };
+            let strategy = Rebalance(steps[..rebalances].to_vec());
+            let results = PortfolioBacktest::new(data, config).run(strategy).unwrap();
+            results.final_portfolio.holdings["BTC"]
+        };
+
+        // 0.5 buys 5/3 BTC, then 0.6 buys another 1/3 and 0.55 sells 1/6 back. Read as
+        // fractions of equity, the 1/3 and 1/6 deltas would trade over a whole BTC.
+        for (rebalances, expected) in [(1, 5.0 / 3.0), (2, 2.0), (3, 11.0 / 6.0)] {
+            let held = held_after(rebalances);
+            assert!((held - expected).abs() < 1e-9, "{rebalances}: {held}");
+        }
+    }
+
+    #[test]
+    fn rebalancing_to_60_40_holds_those_shares_of_equity() {
+        let data = HashMap::from([
+            ("A".to_string(), flat_bars(&[100.0, 101.0, 103.0])),
+            ("B".to_string(), flat_bars(&[50.0, 51.0, 49.0])),
+        ]);
+        let weights = HashMap::from([("A".to_string(), 0.6), ("B".to_string(), 0.4)]);
+        let strategy = Rebalance(vec![weights; 3]);
+
+        let results = PortfolioBacktest::new(data, BacktestConfig::default())
+            .run(strategy)
+            .unwrap();
+
+        let portfolio = &results.final_portfolio;
+        let equity = portfolio.equity();
+        assert!((equity - results.equity_final).abs() < 1e-6);
+        assert!((portfolio.position_value("A") / equity - 0.6).abs() < 1e-9);
+        assert!((portfolio.position_value("B") / equity - 0.4).abs() < 1e-9);
+    }
+
+    fn market(side: OrderSide, symbol: &str, units: f64) -> Order {
+        Order::new(side, OrderType::Market, units, None, None, None, None, None).with_symbol(symbol)
+    }
+
+    /// Submits the orders listed for each bar
+    struct Script(Vec<Vec<Order>>);
+
+    impl PortfolioStrategy for Script {
+        fn next(
+            &mut self,
+            _bars: &HashMap<String, OHLCV>,
+            _portfolio: &PortfolioSnapshot,
+            index: usize,
+        ) -> Result<Vec<Order>> {
+            Ok(self
+                .0
+                .get_mut(index)
+                .map(std::mem::take)
+                .unwrap_or_default())
+        }
+    }
+
+    #[test]
+    fn symbols_pay_their_own_commission_models() {
+        let data = HashMap::from([
+            ("A".to_string(), flat_bars(&[50.0; 2])),
+            ("B".to_string(), flat_bars(&[200.0; 2])),
+        ]);
+        let mut config = BacktestConfig::default();
+        let a_fee: Arc<dyn CommissionModel> = Arc::new(Percentage { rate: 0.001 });
+        let b_fee: Arc<dyn CommissionModel> = Arc::new(PerShare {
+            per_share: 0.01,
+            minimum: 1.0,
+        });
+        config.commission_overrides.insert("A".to_string(), a_fee);
+        config.commission_overrides.insert("B".to_string(), b_fee);
+
+        let strategy = Script(vec![vec![
+            market(OrderSide::Buy, "A", 100.0),
+            market(OrderSide::Buy, "B", 10.0),
+        ]]);
+
+        let results = PortfolioBacktest::new(data, config).run(strategy).unwrap();
+
+        // 0.1% of 5000 for A and the 1.0 minimum for B's 10 shares
+        let cash = results.final_portfolio.cash;
+        assert!(
+            (cash - (10_000.0 - 5_000.0 - 5.0 - 2_000.0 - 1.0)).abs() < 1e-9,
+            "{cash}"
+        );
+    }
+
+    /// Buys 60 of `A` at 100 and 100 of `B` at 50 with room for only 80 of `B`, then
+    /// sells `A` on the second bar
+    fn oversized_second_leg(partial_fills: PartialFills) -> PortfolioResults {
+        let data = HashMap::from([
+            ("A".to_string(), flat_bars(&[100.0; 4])),
+            ("B".to_string(), flat_bars(&[50.0; 4])),
+        ]);
+        let config = BacktestConfig {
+            partial_fills,
+            ..Default::default()
+        };
+        let strategy = Script(vec![
+            vec![
+                market(OrderSide::Buy, "A", 60.0),
+                market(OrderSide::Buy, "B", 100.0),
+            ],
+            vec![market(OrderSide::Sell, "A", 60.0)],
+        ]);
+
+        PortfolioBacktest::new(data, config).run(strategy).unwrap()
+    }
+
+    #[test]
+    fn reject_drops_a_leg_beyond_buying_power_and_reports_it() {
+        let results = oversized_second_leg(PartialFills::Reject);
+
+        assert!(!results.final_portfolio.holdings.contains_key("B"));
+        assert_eq!(results.unfilled_orders.len(), 1);
+        let order = &results.unfilled_orders[0];
+        assert_eq!(order.symbol.as_deref(), Some("B"));
+        assert_eq!(order.filled_size, 0.0);
+    }
+
+    #[test]
+    fn cancel_remainder_fills_what_buying_power_allows() {
+        let results = oversized_second_leg(PartialFills::CancelRemainder);
+
+        assert!((results.final_portfolio.holdings["B"] - 80.0).abs() < 1e-9);
+        assert_eq!(results.unfilled_orders.len(), 1);
+        assert!((results.unfilled_orders[0].remaining_size() - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rest_remainder_fills_the_rest_once_cash_frees_up() {
+        let results = oversized_second_leg(PartialFills::RestRemainder);
+
+        assert!((results.final_portfolio.holdings["B"] - 100.0).abs() < 1e-9);
+        assert!(results.unfilled_orders.is_empty());
+    }
+
+    /// Records the prices the strategy is shown on each bar
+    struct SeenPrices<'a>(&'a mut Vec<HashMap<String, f64>>);
+
+    impl PortfolioStrategy for SeenPrices<'_> {
+        fn next(
+            &mut self,
+            _bars: &HashMap<String, OHLCV>,
+            portfolio: &PortfolioSnapshot,
+            _index: usize,
+        ) -> Result<Vec<Order>> {
+            self.0.push(portfolio.prices.clone());
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn strategy_sees_the_prices_of_the_current_bar() {
+        let data = HashMap::from([
+            ("A".to_string(), flat_bars(&[100.0, 101.0])),
+            ("B".to_string(), flat_bars(&[50.0, 51.0])),
+        ]);
+        let mut seen = Vec::new();
+        PortfolioBacktest::new(data, BacktestConfig::default())
+            .run(SeenPrices(&mut seen))
+            .unwrap();
+
+        let prices = |a: f64, b: f64| HashMap::from([("A".to_string(), a), ("B".to_string(), b)]);
+        assert_eq!(seen, [prices(100.0, 50.0), prices(101.0, 51.0)]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,208 @@
+use crate::error::BacktestError;
+use crate::order::{Order, OrderSide, OrderType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+mod backtest;
+pub use backtest::{PortfolioBacktest, PortfolioResults, PortfolioStrategy};
+
+/// Cash, holdings and prices of a multi-asset account at a point in time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PortfolioSnapshot {
+    pub cash: f64,
+    /// Units held per symbol
+    pub holdings: HashMap<String, f64>,
+    /// Latest price per symbol
+    pub prices: HashMap<String, f64>,
+}
+
+impl PortfolioSnapshot {
+    pub fn new(cash: f64) -> Self {
+        Self {
+            cash,
+            ..Default::default()
+        }
+    }
+
+    /// Market value of the holdings of a single symbol
+    pub fn position_value(&self, symbol: &str) -> f64 {
+        let units = self.holdings.get(symbol).copied().unwrap_or(0.0);
+        let price = self.prices.get(symbol).copied().unwrap_or(0.0);
+        units * price
+    }
+
+    /// Cash plus the market value of all holdings
+    pub fn equity(&self) -> f64 {
+        self.cash
+            + self
+                .holdings
+                .keys()
+                .map(|symbol| self.position_value(symbol))
+                .sum::<f64>()
+    }
+
+    /// Build the orders that move each symbol towards `weight * equity` at the current
+    /// prices.
+    ///
+    /// Symbols that are held but missing from `weights` are sold down to zero.
+    /// Sells are returned before buys, and buys are scaled down proportionally
+    /// when cash plus sale proceeds cannot fund all of them. Fails with
+    /// `BacktestError::MissingPrice` when a weighted or held symbol has no positive
+    /// price.
+    pub fn rebalance_to_weights(
+        &self,
+        weights: &HashMap<String, f64>,
+    ) -> crate::Result<Vec<Order>> {
+        let mut symbols: Vec<&String> = weights.keys().chain(self.holdings.keys()).collect();
+        symbols.sort();
+        symbols.dedup();
+
+        if let Some(symbol) = symbols
+            .iter()
+            .find(|symbol| !self.prices.get(**symbol).is_some_and(|&price| price > 0.0))
+        {
+            return Err(BacktestError::MissingPrice(symbol.to_string()).into());
+        }
+
+        let equity = self.equity();
+
+        let mut sells = Vec::new();
+        let mut buys = Vec::new();
+        let mut available_cash = self.cash;
+
+        for symbol in symbols {
+            let price = self.prices[symbol];
+            let weight = weights.get(symbol).copied().unwrap_or(0.0);
+            let held = self.holdings.get(symbol).copied().unwrap_or(0.0);
+            let delta = weight * equity / price - held;
+
+            if delta < 0.0 {
+                available_cash += -delta * price;
+                sells.push((symbol, -delta));
+            } else if delta > 0.0 {
+                buys.push((symbol, delta, price));
+            }
+        }
+
+        let buy_cost: f64 = buys.iter().map(|(_, units, price)| units * price).sum();
+        let scale = if buy_cost > available_cash && buy_cost > 0.0 {
+            available_cash.max(0.0) / buy_cost
+        } else {
+            1.0
+        };
+
+        let mut orders = Vec::with_capacity(sells.len() + buys.len());
+        for (symbol, units) in sells {
+            orders.push(Self::order_for(symbol, OrderSide::Sell, units));
+        }
+        for (symbol, units, _) in buys {
+            orders.push(Self::order_for(symbol, OrderSide::Buy, units * scale));
+        }
+        Ok(orders)
+    }
+
+    /// Market order for `units` of `symbol`. Deltas below one unit are still units,
+    /// never a fraction of equity.
+    fn order_for(symbol: &str, side: OrderSide, units: f64) -> Order {
+        Order::new(side, OrderType::Market, units, None, None, None, None, None).with_symbol(symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn portfolio(cash: f64, holdings: &[(&str, f64)], prices: &[(&str, f64)]) -> PortfolioSnapshot {
+        let map = |pairs: &[(&str, f64)]| pairs.iter().map(|&(s, v)| (s.to_string(), v)).collect();
+        PortfolioSnapshot {
+            cash,
+            holdings: map(holdings),
+            prices: map(prices),
+        }
+    }
+
+    fn weights(pairs: &[(&str, f64)]) -> HashMap<String, f64> {
+        pairs.iter().map(|&(s, w)| (s.to_string(), w)).collect()
+    }
+
+    /// Holdings after filling `orders` at the snapshot's prices
+    fn apply(portfolio: &PortfolioSnapshot, orders: &[Order]) -> PortfolioSnapshot {
+        let mut after = portfolio.clone();
+        for order in orders {
+            let symbol = order.symbol.clone().unwrap();
+            let units = match order.side {
+                OrderSide::Buy => order.size,
+                OrderSide::Sell => -order.size,
+            };
+            *after.holdings.entry(symbol.clone()).or_default() += units;
+            after.cash -= units * after.prices[&symbol];
+        }
+        after
+    }
+
+    #[test]
+    fn flat_portfolio_rebalances_to_target_values() {
+        let start = portfolio(10_000.0, &[], &[("SPY", 400.0), ("TLT", 95.0)]);
+        let orders = start
+            .rebalance_to_weights(&weights(&[("SPY", 0.6), ("TLT", 0.4)]))
+            .unwrap();
+
+        let after = apply(&start, &orders);
+        assert!((after.position_value("SPY") - 6_000.0).abs() < 1e-6);
+        assert!((after.position_value("TLT") - 4_000.0).abs() < 1e-6);
+        assert!(after.cash.abs() < 1e-6);
+    }
+
+    #[test]
+    fn sells_come_before_buys() {
+        let start = portfolio(0.0, &[("SPY", 25.0)], &[("SPY", 400.0), ("TLT", 100.0)]);
+        let orders = start
+            .rebalance_to_weights(&weights(&[("SPY", 0.5), ("TLT", 0.5)]))
+            .unwrap();
+
+        let sides: Vec<_> = orders.iter().map(|o| o.side).collect();
+        assert_eq!(sides, [OrderSide::Sell, OrderSide::Buy]);
+        assert_eq!(orders[0].symbol.as_deref(), Some("SPY"));
+        assert!((orders[0].size - 12.5).abs() < 1e-9);
+        assert!((orders[1].size - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn buys_scale_down_when_cash_is_short() {
+        let start = portfolio(1_000.0, &[], &[("A", 10.0), ("B", 20.0)]);
+        let orders = start
+            .rebalance_to_weights(&weights(&[("A", 0.8), ("B", 0.8)]))
+            .unwrap();
+
+        // 1600 of buys against 1000 of cash fills each at 1000 / 1600
+        let after = apply(&start, &orders);
+        assert!((after.position_value("A") - 500.0).abs() < 1e-9);
+        assert!((after.position_value("B") - 500.0).abs() < 1e-9);
+        assert!(after.cash.abs() < 1e-9);
+    }
+
+    #[test]
+    fn holdings_missing_from_weights_are_sold_to_zero() {
+        let start = portfolio(0.0, &[("OLD", 10.0)], &[("OLD", 50.0), ("NEW", 25.0)]);
+        let orders = start
+            .rebalance_to_weights(&weights(&[("NEW", 1.0)]))
+            .unwrap();
+
+        let after = apply(&start, &orders);
+        assert!(after.holdings["OLD"].abs() < 1e-9);
+        assert!((after.holdings["NEW"] - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn symbols_without_a_price_are_reported() {
+        let start = portfolio(1_000.0, &[("OLD", 10.0)], &[("SPY", 400.0)]);
+        let error = start
+            .rebalance_to_weights(&weights(&[("SPY", 1.0)]))
+            .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<BacktestError>(),
+            Some(BacktestError::MissingPrice(symbol)) if symbol == "OLD"
+        ));
+    }
+}
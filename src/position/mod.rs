@@ -1,10 +1,41 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use crate::trade::Trade;
 use crate::types::{DirectionalTrade, ProfitLoss, Closeable, StopManagement};
 
+/// Quantity added to a position by a single fill, with its own entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lot {
+    /// Signed size: positive for long, negative for short
+    pub size: f64,
+    pub entry_price: f64,
+    pub entry_time: DateTime<Utc>,
+    pub entry_bar: usize,
+    /// Commission paid on the fill that opened the lot
+    #[serde(default)]
+    pub entry_commission: f64,
+    /// Tag of the order that opened the lot
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+/// Which lots a reducing fill closes first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LotMatching {
+    /// Oldest lot first
+    #[default]
+    Fifo,
+    /// Newest lot first
+    Lifo,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Position {
+    /// Identifier the engine gives the position when it opens, unique within a
+    /// backtest, so an order can close this position alone
+    #[serde(default)]
+    pub id: usize,
     pub size: f64,
     pub entry_price: f64,
     pub entry_time: DateTime<Utc>,
@@ -12,11 +43,27 @@ pub struct Position {
     pub sl: Option<f64>,
     pub tp: Option<f64>,
     pub tag: Option<String>,
+    /// Instrument held, so exits apply its cost overrides
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// Trailing stop distance as a fraction of the best price since entry
+    pub trail_percent: Option<f64>,
+    /// Trailing stop distance in price units from the best price since entry
+    pub trail_amount: Option<f64>,
+    /// Initial margin set aside for the position at its entry prices
+    pub margin_used: f64,
+    /// Index of the bar the position was opened on
+    pub entry_bar: usize,
+    /// Fills that built the position, oldest first. Empty means the position is a
+    /// single lot described by `size`, `entry_price`, `entry_time` and `entry_bar`.
+    #[serde(default)]
+    pub lots: VecDeque<Lot>,
 }
 
 impl Position {
     pub fn new(size: f64, entry_price: f64, entry_time: DateTime<Utc>) -> Self {
         Self {
+            id: 0,
             size,
             entry_price,
             entry_time,
@@ -24,6 +71,12 @@ impl Position {
             sl: None,
             tp: None,
             tag: None,
+            symbol: None,
+            trail_percent: None,
+            trail_amount: None,
+            margin_used: 0.0,
+            entry_bar: 0,
+            lots: VecDeque::new(),
         }
     }
 
@@ -36,6 +89,7 @@ impl Position {
         tag: Option<String>,
     ) -> Self {
         Self {
+            id: 0,
             size,
             entry_price,
             entry_time,
@@ -43,6 +97,12 @@ impl Position {
             sl,
             tp,
             tag,
+            symbol: None,
+            trail_percent: None,
+            trail_amount: None,
+            margin_used: 0.0,
+            entry_bar: 0,
+            lots: VecDeque::new(),
         }
     }
 
@@ -80,10 +140,138 @@ impl Position {
         self.current_price = price;
     }
 
+    /// Ratchet the stop loss behind the bar's extreme in the position's favour.
+    /// The stop only ever tightens; with both trail settings the tighter one wins.
+    pub fn trail_stop(&mut self, high: f64, low: f64) {
+        let candidates = if self.is_long() {
+            [
+                self.trail_percent.map(|p| high * (1.0 - p)),
+                self.trail_amount.map(|a| high - a),
+            ]
+        } else {
+            [
+                self.trail_percent.map(|p| low * (1.0 + p)),
+                self.trail_amount.map(|a| low + a),
+            ]
+        };
+
+        for stop in candidates.into_iter().flatten() {
+            self.sl = Some(match self.sl {
+                Some(sl) if self.is_long() => sl.max(stop),
+                Some(sl) => sl.min(stop),
+                None => stop,
+            });
+        }
+    }
+
+    /// Add `size` units (signed) at `entry_price` as a new lot, moving `entry_price`
+    /// to the weighted average cost of all lots
+    pub fn add_lot(
+        &mut self,
+        size: f64,
+        entry_price: f64,
+        entry_time: DateTime<Utc>,
+        entry_bar: usize,
+        entry_commission: f64,
+        tag: Option<String>,
+    ) {
+        self.materialize_lot();
+        self.lots.push_back(Lot {
+            size,
+            entry_price,
+            entry_time,
+            entry_bar,
+            entry_commission,
+            tag,
+        });
+        self.refresh_from_lots();
+    }
+
+    /// Remove `size` units from the lots in `matching` order, splitting a lot when
+    /// only part of it is needed, and return the removed quantities
+    pub fn take_lots(&mut self, size: f64, matching: LotMatching) -> Vec<Lot> {
+        self.materialize_lot();
+        let mut remaining = size.abs().min(self.size.abs());
+        let mut taken = Vec::new();
+
+        while remaining > f64::EPSILON {
+            let lot = match matching {
+                LotMatching::Fifo => self.lots.front_mut(),
+                LotMatching::Lifo => self.lots.back_mut(),
+            };
+            let Some(lot) = lot else { break };
+
+            if lot.size.abs() <= remaining + f64::EPSILON {
+                remaining -= lot.size.abs();
+                let lot = match matching {
+                    LotMatching::Fifo => self.lots.pop_front(),
+                    LotMatching::Lifo => self.lots.pop_back(),
+                };
+                taken.extend(lot);
+            } else {
+                let part = remaining * lot.size.signum();
+                let part_commission = lot.entry_commission * part / lot.size;
+                lot.size -= part;
+                lot.entry_commission -= part_commission;
+                taken.push(Lot {
+                    size: part,
+                    entry_commission: part_commission,
+                    ..lot.clone()
+                });
+                remaining = 0.0;
+            }
+        }
+
+        let taken_size: f64 = taken.iter().map(|lot| lot.size.abs()).sum();
+        if self.size != 0.0 {
+            self.margin_used *= (1.0 - taken_size / self.size.abs()).max(0.0);
+        }
+        self.refresh_from_lots();
+        taken
+    }
+
+    /// Turn an implicit single-lot position into an explicit lot
+    fn materialize_lot(&mut self) {
+        if self.lots.is_empty() && self.size != 0.0 {
+            self.lots.push_back(Lot {
+                size: self.size,
+                entry_price: self.entry_price,
+                entry_time: self.entry_time,
+                entry_bar: self.entry_bar,
+                entry_commission: 0.0,
+                tag: self.tag.clone(),
+            });
+        }
+    }
+
+    /// Recompute size, average entry price and earliest entry from the lots
+    fn refresh_from_lots(&mut self) {
+        self.size = self.lots.iter().map(|lot| lot.size).sum();
+        if self.size.abs() < f64::EPSILON {
+            self.size = 0.0;
+            self.lots.clear();
+            return;
+        }
+
+        let cost: f64 = self.lots.iter().map(|lot| lot.size * lot.entry_price).sum();
+        self.entry_price = cost / self.size;
+        if let Some(first) = self.lots.iter().min_by_key(|lot| lot.entry_bar) {
+            self.entry_time = first.entry_time;
+            self.entry_bar = first.entry_bar;
+        }
+    }
+
     pub fn close(&mut self, portion: f64, exit_price: f64, exit_time: DateTime<Utc>, exit_bar: Option<usize>) -> Trade {
         let portion = portion.clamp(0.0, 1.0);
         let closed_size = self.size * portion;
         self.size -= closed_size;
+        self.margin_used *= 1.0 - portion;
+        let mut closed_commission = 0.0;
+        for lot in self.lots.iter_mut() {
+            closed_commission += lot.entry_commission * portion;
+            lot.size *= 1.0 - portion;
+            lot.entry_commission *= 1.0 - portion;
+        }
 
         let mut trade = Trade::new(
             exit_bar.unwrap_or(0),
@@ -94,6 +282,7 @@ impl Position {
             self.tp,
             self.tag.clone(),
         );
+        trade.entry_commission = closed_commission;
         trade.close(exit_bar, exit_price, exit_time);
         trade
     }
@@ -0,0 +1,157 @@
+use crate::order::Order;
+use crate::position::Position;
+use crate::types::CalendarPeriod;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Per-bar view of the engine handed to strategies
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Context {
+    timestamp: Option<DateTime<Utc>>,
+    previous_timestamp: Option<DateTime<Utc>>,
+    recorded_indicators: Vec<(String, f64)>,
+    positions: Vec<Position>,
+    cash: f64,
+    equity: f64,
+    submitted_orders: Vec<Order>,
+    open_orders: Vec<Order>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the context onto the next bar
+    pub(crate) fn advance(&mut self, timestamp: DateTime<Utc>) {
+        self.previous_timestamp = self.timestamp;
+        self.timestamp = Some(timestamp);
+    }
+
+    /// Refresh the broker state the strategy sees on this bar. The engine's positions
+    /// are moved in and handed back with `take_positions` once the strategy is done.
+    pub(crate) fn sync_broker(&mut self, positions: Vec<Position>, cash: f64, equity: f64) {
+        self.positions = positions;
+        self.cash = cash;
+        self.equity = equity;
+    }
+
+    /// Hand the positions lent by `sync_broker` back to the engine
+    pub(crate) fn take_positions(&mut self) -> Vec<Position> {
+        std::mem::take(&mut self.positions)
+    }
+
+    /// Refresh the resting orders the strategy sees on this bar
+    pub(crate) fn sync_orders(&mut self, pending: &[Order]) {
+        self.open_orders.clear();
+        self.open_orders.extend_from_slice(pending);
+    }
+
+    /// Open position before this bar's orders are processed; the oldest one when
+    /// `BacktestConfig::hedging` allows several
+    pub fn position(&self) -> Option<&Position> {
+        self.positions.first()
+    }
+
+    /// Every open position before this bar's orders are processed, oldest first
+    pub fn positions(&self) -> &[Position] {
+        &self.positions
+    }
+
+    /// Net signed size of the open positions, 0.0 when flat
+    pub fn position_size(&self) -> f64 {
+        self.positions.iter().fold(0.0, |net, p| net + p.size)
+    }
+
+    /// Cash available before this bar's orders are processed
+    pub fn cash(&self) -> f64 {
+        self.cash
+    }
+
+    /// Equity with the open position marked at the previous bar's close
+    pub fn equity(&self) -> f64 {
+        self.equity
+    }
+
+    /// Queue an order to be processed with the ones returned from `next`
+    pub fn submit(&mut self, order: Order) {
+        self.submitted_orders.push(order);
+    }
+
+    /// Close `fraction` (0.0 to 1.0) of the open position at this bar's fill price.
+    ///
+    /// The closed part is recorded as a trade; the rest stays open with its original
+    /// entry price and time.
+    pub fn close_position(&mut self, fraction: f64) {
+        self.submit(Order::close_portion(fraction));
+    }
+
+    /// Close `fraction` (0.0 to 1.0) of the open position with `Position::id` `id`,
+    /// leaving any other positions open
+    pub fn close_position_by_id(&mut self, id: usize, fraction: f64) {
+        self.submit(Order::close_position(id, fraction));
+    }
+
+    /// Orders from earlier bars still waiting to fill, in submission order: limit and
+    /// stop orders this bar didn't reach, and market orders deferred to the next open
+    /// by `FillTiming::NextBarOpen`. Cancelled ones stay in the list until the bar ends.
+    pub fn open_orders(&self) -> &[Order] {
+        &self.open_orders
+    }
+
+    /// Cancel the resting order at `index` in `open_orders`, so it never fills.
+    /// Out-of-range indices are ignored.
+    pub fn cancel_order(&mut self, index: usize) {
+        if let Some(order) = self.open_orders.get_mut(index) {
+            order.cancel();
+        }
+    }
+
+    /// Cancel every resting order tagged `tag`, returning how many were cancelled
+    pub fn cancel_orders_with_tag(&mut self, tag: &str) -> usize {
+        let mut cancelled = 0;
+        for order in &mut self.open_orders {
+            if order.tag.as_deref() == Some(tag) && !order.is_cancelled() {
+                order.cancel();
+                cancelled += 1;
+            }
+        }
+        cancelled
+    }
+
+    /// Cancel every resting order
+    pub fn cancel_all_orders(&mut self) {
+        self.open_orders.iter_mut().for_each(Order::cancel);
+    }
+
+    /// Take the orders submitted during the current bar
+    pub(crate) fn take_submitted_orders(&mut self) -> Vec<Order> {
+        std::mem::take(&mut self.submitted_orders)
+    }
+
+    /// Timestamp of the bar currently being processed
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.timestamp
+    }
+
+    /// Whether the current bar is the first one of a new calendar period.
+    /// The very first bar of the backtest counts as the start of its period.
+    pub fn is_first_bar_of(&self, period: CalendarPeriod) -> bool {
+        match (self.previous_timestamp, self.timestamp) {
+            (Some(previous), Some(current)) => period.is_new_period(previous, current),
+            (None, Some(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Record an indicator value for the current bar so it is returned
+    /// alongside the backtest results in `indicator_series`
+    pub fn record_indicator(&mut self, name: &str, value: f64) {
+        self.recorded_indicators.push((name.to_string(), value));
+    }
+
+    /// Take the indicator values recorded during the current bar
+    pub(crate) fn take_recorded_indicators(&mut self) -> Vec<(String, f64)> {
+        std::mem::take(&mut self.recorded_indicators)
+    }
+}
@@ -1,23 +1,61 @@
+use crate::backtest::BacktestResults;
 use crate::order::Order;
 use crate::trade::Trade;
 use crate::types::OHLCV;
 use crate::Result;
 
+/// Fraction of equity `buy` and `sell` deploy when no size is given
+const DEFAULT_EQUITY_FRACTION: f64 = 0.9999;
+
+mod context;
+pub use context::Context;
+
 pub trait Strategy {
     /// Initialize the strategy with historical data
     /// This is called once before the backtest begins
     fn init(&mut self, data: &[OHLCV]) -> Result<()>;
 
+    /// Minimum number of bars the strategy needs to produce signals
+    /// The backtest fails with `BacktestError::InsufficientData` on shorter data
+    fn min_bars(&self) -> usize {
+        0
+    }
+
     /// Called for each bar of data during the backtest
-    /// Implement your trading logic here
-    fn next(&mut self, bar: &OHLCV, index: usize) -> Result<Vec<Order>>;
+    /// Implement your trading logic here, or in `next_with_context` instead
+    fn next(&mut self, _bar: &OHLCV, _index: usize) -> Result<Vec<Order>> {
+        Ok(vec![])
+    }
+
+    /// Called by the engine for each bar with access to the backtest context
+    /// Defaults to `next`, override it when the strategy needs the position, cash,
+    /// equity or other broker state exposed by the context
+    fn next_with_context(
+        &mut self,
+        _ctx: &mut Context,
+        bar: &OHLCV,
+        index: usize,
+    ) -> Result<Vec<Order>> {
+        self.next(bar, index)
+    }
 
     /// Optional: Called when a trade is closed
     fn on_trade_close(&mut self, _trade: &Trade) -> Result<()> {
         Ok(())
     }
 
+    /// Optional: Called once after the last bar with the backtest results
+    /// Use it to summarize or write out anything the strategy collected along the way
+    fn on_finish(&mut self, _results: &BacktestResults) -> Result<()> {
+        Ok(())
+    }
+
     /// Place a new long order
+    ///
+    /// As in backtesting.py, a `size` between 0 and 1 is a fraction of the equity at
+    /// fill time, so `Some(0.5)` deploys half of it, and a `size` of 1 or more is a
+    /// number of units. Without a size the order deploys `0.9999` of equity, nearly
+    /// all of it. Build the order with `Order::new` to trade fewer than one unit.
     fn buy(
         &mut self,
         size: Option<f64>,
@@ -29,23 +67,39 @@ pub trait Strategy {
     ) -> Order {
         use crate::order::{OrderSide, OrderType};
 
-        Order::new(
+        let order = Order::new(
             OrderSide::Buy,
-            if limit.is_some() {
-                OrderType::Limit
-            } else {
-                OrderType::Market
-            },
-            size.unwrap_or(0.9999),
+            OrderType::for_prices(limit, stop),
+            size.unwrap_or_default(),
             limit,
             stop,
             sl,
             tp,
             tag,
-        )
+        );
+        sized(order, size)
+    }
+
+    /// Close the whole open position, whatever its size
+    fn close(&mut self) -> Order {
+        Order::flatten()
+    }
+
+    /// Place a long market order protected by a stop trailing `trail_percent` below
+    /// the highest price since entry
+    fn buy_trailing(
+        &mut self,
+        size: Option<f64>,
+        trail_percent: f64,
+        tp: Option<f64>,
+        tag: Option<String>,
+    ) -> Order {
+        self.buy(size, None, None, None, tp, tag).with_trail_percent(trail_percent)
     }
 
     /// Place a new short order
+    ///
+    /// `size` follows the same rules as in [`Strategy::buy`].
     fn sell(
         &mut self,
         size: Option<f64>,
@@ -57,20 +111,26 @@ pub trait Strategy {
     ) -> Order {
         use crate::order::{OrderSide, OrderType};
 
-        Order::new(
+        let order = Order::new(
             OrderSide::Sell,
-            if limit.is_some() {
-                OrderType::Limit
-            } else {
-                OrderType::Market
-            },
-            size.unwrap_or(0.9999),
+            OrderType::for_prices(limit, stop),
+            size.unwrap_or_default(),
             limit,
             stop,
             sl,
             tp,
             tag,
-        )
+        );
+        sized(order, size)
+    }
+}
+
+/// Size `order` as `buy` and `sell` document: fractions below one are a share of equity
+fn sized(order: Order, size: Option<f64>) -> Order {
+    match size {
+        Some(size) if size > 0.0 && size < 1.0 => order.with_equity_fraction(size),
+        Some(_) => order,
+        None => order.with_equity_fraction(DEFAULT_EQUITY_FRACTION),
     }
 }
 
@@ -0,0 +1,27 @@
+//! Bar builders shared by the unit tests
+
+use crate::types::OHLCV;
+use chrono::{Duration, TimeZone, Utc};
+
+/// Daily bars from `(open, high, low, close)` prices, starting 2023-01-02
+pub fn bars(prices: &[(f64, f64, f64, f64)]) -> Vec<OHLCV> {
+    let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+    prices
+        .iter()
+        .enumerate()
+        .map(|(i, &(open, high, low, close))| OHLCV {
+            timestamp: start + Duration::days(i as i64),
+            open,
+            high,
+            low,
+            close,
+            volume: 1000.0,
+        })
+        .collect()
+}
+
+/// Daily bars that trade at a single price each
+pub fn flat_bars(prices: &[f64]) -> Vec<OHLCV> {
+    let prices: Vec<_> = prices.iter().map(|&p| (p, p, p, p)).collect();
+    bars(&prices)
+}
\ No newline at end of file
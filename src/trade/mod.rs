@@ -14,6 +14,12 @@ pub struct Trade {
     pub sl: Option<f64>,
     pub tp: Option<f64>,
     pub tag: Option<String>,
+    /// Commission paid to open the trade
+    #[serde(default)]
+    pub entry_commission: f64,
+    /// Commission paid to close the trade
+    #[serde(default)]
+    pub exit_commission: f64,
 }
 
 impl Trade {
@@ -37,6 +43,8 @@ impl Trade {
             sl,
             tp,
             tag,
+            entry_commission: 0.0,
+            exit_commission: 0.0,
         }
     }
 
@@ -52,7 +60,17 @@ impl Trade {
         self.exit_price.is_some()
     }
 
+    /// Realized P&L net of entry and exit commissions
     pub fn pl(&self) -> f64 {
+        if self.is_closed() {
+            self.gross_pl() - self.commissions()
+        } else {
+            0.0 // Active trade, no realized P&L yet
+        }
+    }
+
+    /// Realized P&L from price movement alone, before commissions
+    pub fn gross_pl(&self) -> f64 {
         match self.exit_price {
             Some(exit_price) => {
                 if self.is_long() {
@@ -65,20 +83,46 @@ impl Trade {
         }
     }
 
+    /// Realized return on the entry notional, net of commissions
     pub fn pl_pct(&self) -> f64 {
+        let Some(exit_price) = self.exit_price else {
+            return 0.0; // Active trade, no realized P&L yet
+        };
         if self.entry_price == 0.0 {
-            0.0
+            return 0.0;
+        }
+
+        let gross_pct = if self.is_long() {
+            (exit_price - self.entry_price) / self.entry_price
         } else {
-            match self.exit_price {
-                Some(exit_price) => {
-                    if self.is_long() {
-                        (exit_price - self.entry_price) / self.entry_price
-                    } else {
-                        (self.entry_price - exit_price) / self.entry_price
-                    }
-                }
-                None => 0.0, // Active trade, no realized P&L yet
-            }
+            (self.entry_price - exit_price) / self.entry_price
+        };
+        let entry_notional = self.entry_price * self.size.abs();
+        if entry_notional == 0.0 {
+            gross_pct
+        } else {
+            gross_pct - self.commissions() / entry_notional
+        }
+    }
+
+    /// Total commission paid on entry and exit
+    pub fn commissions(&self) -> f64 {
+        self.entry_commission + self.exit_commission
+    }
+
+    /// Realized P&L as a multiple of the initial risk `|entry_price - sl| * size`
+    /// Returns `None` for open trades and trades without a stop loss
+    pub fn r_multiple(&self) -> Option<f64> {
+        let sl = self.sl?;
+        if !self.is_closed() {
+            return None;
+        }
+
+        let risk = (self.entry_price - sl).abs() * self.size.abs();
+        if risk > 0.0 {
+            Some(self.pl() / risk)
+        } else {
+            None
         }
     }
 
@@ -96,6 +140,11 @@ impl Trade {
         }
     }
 
+    /// Number of bars the trade was held, `None` while it is open
+    pub fn duration_bars(&self) -> Option<usize> {
+        self.exit_bar.map(|exit_bar| exit_bar.saturating_sub(self.entry_bar))
+    }
+
     pub fn close(&mut self, exit_bar: Option<usize>, exit_price: f64, exit_time: DateTime<Utc>) {
         self.exit_bar = exit_bar;
         self.exit_price = Some(exit_price);
@@ -106,11 +155,13 @@ impl Trade {
         let portion = portion.clamp(0.0, 1.0);
         let closed_size = self.size * portion;
 
-        // Reduce current trade size
+        // Reduce current trade size and its share of the entry commission
         self.size -= closed_size;
+        let closed_commission = self.entry_commission * portion;
+        self.entry_commission -= closed_commission;
 
         // Create new trade for the closed portion
-        Trade::new(
+        let mut trade = Trade::new(
             self.entry_bar,
             self.entry_price,
             self.entry_time,
@@ -118,11 +169,13 @@ impl Trade {
             self.sl,
             self.tp,
             self.tag.clone(),
-        )
+        );
+        trade.entry_commission = closed_commission;
+        trade
     }
 
     pub fn get_exit_time(&self) -> DateTime<Utc> {
-        self.exit_time.unwrap_or_else(|| Utc::now()) // Use exit time if available, otherwise current time
+        self.exit_time.unwrap_or_else(Utc::now) // Use exit time if available, otherwise current time
     }
 
     pub fn get_exit_price(&self, current_price: f64) -> f64 {
@@ -146,33 +199,11 @@ impl DirectionalTrade for Trade {
 
 impl ProfitLoss for Trade {
     fn pl(&self) -> f64 {
-        match self.exit_price {
-            Some(exit_price) => {
-                if self.is_long() {
-                    (exit_price - self.entry_price) * self.size
-                } else {
-                    (self.entry_price - exit_price) * self.size.abs()
-                }
-            }
-            None => 0.0, // Active trade, no realized P&L yet
-        }
+        Trade::pl(self)
     }
 
     fn pl_pct(&self) -> f64 {
-        if self.entry_price == 0.0 {
-            0.0
-        } else {
-            match self.exit_price {
-                Some(exit_price) => {
-                    if self.is_long() {
-                        (exit_price - self.entry_price) / self.entry_price
-                    } else {
-                        (self.entry_price - exit_price) / self.entry_price
-                    }
-                }
-                None => 0.0, // Active trade, no realized P&L yet
-            }
-        }
+        Trade::pl_pct(self)
     }
 }
 
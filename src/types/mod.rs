@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -44,6 +44,47 @@ impl Default for OHLCV {
     }
 }
 
+/// Calendar period used for periodic scheduling and assessments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CalendarPeriod {
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl CalendarPeriod {
+    /// Returns true when `current` falls in a different period than `previous`
+    pub fn is_new_period(&self, previous: DateTime<Utc>, current: DateTime<Utc>) -> bool {
+        match self {
+            CalendarPeriod::Week => previous.iso_week() != current.iso_week(),
+            CalendarPeriod::Month => {
+                (previous.year(), previous.month()) != (current.year(), current.month())
+            }
+            CalendarPeriod::Quarter => {
+                (previous.year(), previous.month0() / 3) != (current.year(), current.month0() / 3)
+            }
+            CalendarPeriod::Year => previous.year() != current.year(),
+        }
+    }
+
+    /// First day of the period containing `timestamp`; weeks start on Monday
+    pub fn start_date(&self, timestamp: DateTime<Utc>) -> NaiveDate {
+        let date = timestamp.date_naive();
+        let first_of_month = |month0: u32| {
+            NaiveDate::from_ymd_opt(date.year(), month0 + 1, 1).expect("valid first of month")
+        };
+        match self {
+            CalendarPeriod::Week => {
+                date - Duration::days(date.weekday().num_days_from_monday() as i64)
+            }
+            CalendarPeriod::Month => first_of_month(date.month0()),
+            CalendarPeriod::Quarter => first_of_month(date.month0() / 3 * 3),
+            CalendarPeriod::Year => first_of_month(0),
+        }
+    }
+}
+
 /// Common trait for trading instruments that have position direction
 pub trait DirectionalTrade {
     fn is_long(&self) -> bool;